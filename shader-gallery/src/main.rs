@@ -0,0 +1,318 @@
+use shared::cpu_render::{CpuImage, ShaderFn, render, write_png_rgba};
+use shared_nostd::registry::ShaderEntry;
+use std::{env, fmt::Write, fs, io, path::PathBuf};
+
+const COLLECTIONS: [(&str, &[ShaderEntry]); 2] = [
+	("textures/tests", tex_tests_shader::ENTRIES),
+	("textures/sketches", tex_sketches_shader::ENTRIES),
+];
+
+const USAGE: &str = "Usage: shader-gallery [options]
+
+Options:
+  --out <dir>         output directory (default: target/gallery)
+  --size <w>x<h>      size of a frame (default: 256x256)
+  --times <t,t,...>   times in seconds to render, the first one is the thumbnail
+                      (default: 0,0.5,1,1.5,2,2.5,3,3.5)
+  --filter <text>     only render entry points containing the text";
+
+struct Options {
+	out: PathBuf,
+	width: u32,
+	height: u32,
+	times: Vec<f32>,
+	filter: Option<String>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Options, String> {
+	let mut options = Options {
+		out: PathBuf::from("target/gallery"),
+		width: 256,
+		height: 256,
+		times: vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5],
+		filter: None,
+	};
+
+	let mut args = args;
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+		match arg.as_str() {
+			"--out" => options.out = PathBuf::from(value()?),
+			"--size" => {
+				let v = value()?;
+				let (w, h) = v
+					.split_once('x')
+					.and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+					.filter(|&(w, h)| w > 0 && h > 0)
+					.ok_or(format!("invalid size {v}"))?;
+				options.width = w;
+				options.height = h;
+			}
+			"--times" => {
+				let v = value()?;
+				options.times = v
+					.split(',')
+					.map(|t| t.trim().parse::<f32>())
+					.collect::<Result<_, _>>()
+					.map_err(|_| format!("invalid times {v}"))?;
+			}
+			"--filter" => options.filter = Some(value()?),
+			_ => return Err(format!("unknown argument {arg}")),
+		}
+	}
+
+	Ok(options)
+}
+
+struct Entry {
+	collection: &'static str,
+	entry: &'static ShaderEntry,
+	/// Not set for entry points that can't be evaluated on the CPU.
+	thumbnail: Option<String>,
+	/// Set for animated entry points.
+	strip: Option<String>,
+}
+
+/// Places the frames next to each other in one image.
+fn frame_strip(frames: &[CpuImage]) -> (u32, u32, Vec<u8>) {
+	let (width, height) = (frames[0].width, frames[0].height);
+	let rows: Vec<Vec<u8>> = frames.iter().map(|f| f.to_rgba_u8()).collect();
+	let row_len = width as usize * 4;
+	let mut rgba = Vec::with_capacity(rows.len() * rows[0].len());
+	for y in 0..height as usize {
+		for frame in &rows {
+			rgba.extend_from_slice(&frame[y * row_len..(y + 1) * row_len]);
+		}
+	}
+	(width * frames.len() as u32, height, rgba)
+}
+
+fn render_entry(
+	options: &Options,
+	collection: &'static str,
+	entry: &'static ShaderEntry,
+	shader: ShaderFn,
+) -> io::Result<Entry> {
+	let times = if entry.animated {
+		&options.times[..]
+	} else {
+		&options.times[..1]
+	};
+	let frames: Vec<CpuImage> = times
+		.iter()
+		.map(|&t| render(options.width, options.height, t, shader))
+		.collect();
+
+	let dir = collection.replace('/', "_");
+	let name = entry.name;
+	let thumbnail = format!("{dir}/{name}.png");
+	frames[0].write_png(options.out.join(&thumbnail))?;
+
+	let strip = if frames.len() > 1 {
+		let strip = format!("{dir}/{name}.strip.png");
+		let (w, h, rgba) = frame_strip(&frames);
+		write_png_rgba(options.out.join(&strip), w, h, &rgba)?;
+		Some(strip)
+	} else {
+		None
+	};
+
+	Ok(Entry {
+		collection,
+		entry,
+		thumbnail: Some(thumbnail),
+		strip,
+	})
+}
+
+fn write_html(options: &Options, entries: &[Entry]) -> String {
+	let (w, h) = (options.width, options.height);
+	let frames = options.times.len();
+	let times = options
+		.times
+		.iter()
+		.map(|t| t.to_string())
+		.collect::<Vec<_>>()
+		.join(", ");
+
+	let mut html = String::new();
+	let _ = write!(
+		html,
+		r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Shader gallery</title>
+<style>
+body {{ font-family: sans-serif; background: #1b1b1b; color: #ddd; margin: 2em; }}
+.grid {{ display: flex; flex-wrap: wrap; gap: 1.5em; }}
+figure {{ margin: 0; width: {w}px; }}
+figcaption {{ font-size: 0.85em; line-height: 1.4; }}
+code {{ color: #fff; }}
+.preview {{ width: {w}px; height: {h}px; background-size: auto 100%; }}
+.missing {{ display: flex; align-items: center; justify-content: center; background: #333; }}
+.animated {{ animation: play {duration}s steps({frames}) infinite; }}
+@keyframes play {{ to {{ background-position: -{strip_w}px 0; }} }}
+details img {{ width: 100%; image-rendering: pixelated; }}
+</style>
+</head>
+<body>
+<h1>Shader gallery</h1>
+<p>{count} entry points, {w}x{h} pixels, rendered on the CPU at t = {times}.</p>
+"#,
+		duration = frames as f32 * 0.25,
+		strip_w = w as usize * frames,
+		count = entries.len(),
+	);
+
+	for (collection, _) in COLLECTIONS {
+		let in_collection: Vec<_> = entries
+			.iter()
+			.filter(|e| e.collection == collection)
+			.collect();
+		if in_collection.is_empty() {
+			continue;
+		}
+
+		let _ = writeln!(html, "<h2>{collection}</h2>\n<div class=\"grid\">");
+		for e in in_collection {
+			let _ = writeln!(html, "<figure>");
+			let _ = match (&e.strip, &e.thumbnail) {
+				(Some(preview), _) => writeln!(
+					html,
+					"<div class=\"preview animated\" style=\"background-image: url('{preview}')\"></div>"
+				),
+				(None, Some(preview)) => writeln!(
+					html,
+					"<div class=\"preview\" style=\"background-image: url('{preview}')\"></div>"
+				),
+				(None, None) => writeln!(
+					html,
+					"<div class=\"preview missing\">not renderable on the CPU</div>"
+				),
+			};
+
+			let kind = if e.entry.animated {
+				"animated"
+			} else {
+				"static"
+			};
+			let _ = write!(
+				html,
+				"<figcaption><code>{}(uv, {})</code><br>{kind}",
+				e.entry.name,
+				e.entry.bindings.names().join(", ")
+			);
+			if let Some(thumbnail) = &e.thumbnail {
+				let _ = write!(
+					html,
+					"<br><a href=\"{thumbnail}\">thumbnail</a> at t = {}",
+					options.times[0]
+				);
+			}
+			let _ = writeln!(html, "</figcaption>");
+			if let Some(strip) = &e.strip {
+				let _ = writeln!(
+					html,
+					"<details><summary>frames at t = {times}</summary><img src=\"{strip}\"></details>"
+				);
+			}
+			let _ = writeln!(html, "</figure>");
+		}
+		let _ = writeln!(html, "</div>");
+	}
+
+	html.push_str("</body>\n</html>\n");
+	html
+}
+
+fn main() -> io::Result<()> {
+	let options = match parse_args(env::args().skip(1)) {
+		Ok(options) => options,
+		Err(e) => {
+			eprintln!("{e}\n\n{USAGE}");
+			std::process::exit(1);
+		}
+	};
+
+	let mut entries = vec![];
+	for (collection, shader_entries) in COLLECTIONS {
+		for entry in shader_entries {
+			if options
+				.filter
+				.as_ref()
+				.is_some_and(|f| !entry.name.contains(f.as_str()))
+			{
+				continue;
+			}
+			match entry.cpu {
+				Some(shader) => {
+					println!("Rendering {collection}/{}", entry.name);
+					entries.push(render_entry(&options, collection, entry, shader)?);
+				}
+				None => {
+					println!("Skipping {collection}/{}, it binds textures", entry.name);
+					entries.push(Entry {
+						collection,
+						entry,
+						thumbnail: None,
+						strip: None,
+					});
+				}
+			}
+		}
+	}
+
+	fs::create_dir_all(&options.out)?;
+	let index = options.out.join("index.html");
+	fs::write(&index, write_html(&options, &entries))?;
+	println!("Wrote {}", index.display());
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parse(args: &[&str]) -> Result<Options, String> {
+		parse_args(args.iter().map(|a| a.to_string()))
+	}
+
+	#[test]
+	fn parses_options() {
+		let o = parse(&[]).unwrap();
+		assert_eq!((o.width, o.height, o.times.len()), (256, 256, 8));
+
+		let o = parse(&["--size", "64x32", "--times", "0, 1.5", "--filter", "bos"]).unwrap();
+		assert_eq!((o.width, o.height), (64, 32));
+		assert_eq!(o.times, [0.0, 1.5]);
+		assert_eq!(o.filter.as_deref(), Some("bos"));
+	}
+
+	#[test]
+	fn rejects_invalid_sizes() {
+		for size in ["64", "0x32", "64x0", "ax32", "64x-1", ""] {
+			assert_eq!(
+				parse(&["--size", size]).err(),
+				Some(format!("invalid size {size}"))
+			);
+		}
+		assert_eq!(
+			parse(&["--size"]).err().as_deref(),
+			Some("missing value for --size")
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_times() {
+		for times in ["", "1,,2", "0,a", "1;2"] {
+			assert_eq!(
+				parse(&["--times", times]).err(),
+				Some(format!("invalid times {times}"))
+			);
+		}
+		assert!(parse(&["--times"]).is_err());
+		assert!(parse(&["--frames", "4"]).is_err());
+	}
+}
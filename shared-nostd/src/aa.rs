@@ -0,0 +1,143 @@
+// Anti-aliased coverage for distance values, with the filter width derived from the canvas
+// size instead of a hand tuned smoothness. Edges stay one pixel wide at every resolution.
+
+use crate::sdf::fill;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{UVec2, Vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{UVec2, Vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+/// Size of one pixel in the space of `aspect_preserving_uv`, where the longer side of the
+/// canvas spans one unit.
+pub fn pixel_size(size: UVec2) -> f32 {
+	1.0 / size.x.max(size.y).max(1) as f32
+}
+
+/// Size of one pixel in plain UV space, which is not square on non-square canvases.
+pub fn pixel_size_uv(size: UVec2) -> Vec2 {
+	1.0 / size.max(UVec2::ONE).as_vec2()
+}
+
+/// Filter width for a distance field with the `gradient` of the distance in plain UV
+/// space. Unit gradients along x and y give the pixel width and height.
+pub fn footprint(gradient: Vec2, size: UVec2) -> f32 {
+	(gradient * pixel_size_uv(size)).abs().element_sum()
+}
+
+/// Coverage of a shape whose distance `d` is measured in `aspect_preserving_uv` units.
+pub fn coverage(d: f32, size: UVec2) -> f32 {
+	fill(d, pixel_size(size))
+}
+
+/// Like `coverage` for distances measured after scaling the UV by `scale`, e.g. the
+/// number of tiles.
+pub fn coverage_scaled(d: f32, size: UVec2, scale: f32) -> f32 {
+	fill(d, pixel_size(size) * scale)
+}
+
+/// Coverage of the outline with a stroke `width` given in pixels.
+pub fn stroke_pixels(d: f32, width: f32, size: UVec2) -> f32 {
+	let px = pixel_size(size);
+	fill(d.abs() - width * px * 0.5, px)
+}
+
+/// Coverage with the filter width taken from the screen space derivatives of `d`, which
+/// also covers distorted or non-uniformly scaled distance fields. Pass `fwidth(d)` from the
+/// fragment shader. The derivative functions depend on the spirv-std version, so callers
+/// compute them and this stays usable and testable on the CPU.
+pub fn coverage_fwidth(d: f32, fwidth: f32) -> f32 {
+	fill(d, fwidth.max(1e-6))
+}
+
+/// Like `coverage_fwidth` with the screen space gradient `(ddx(d), ddy(d))`. Its euclidean
+/// length is more precise on diagonal edges than the sum in `fwidth`.
+pub fn coverage_gradient(d: f32, gradient: Vec2) -> f32 {
+	fill(d, gradient.length().max(1e-6))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::aspect_preserving_uv;
+	use glam::{uvec2, vec2};
+
+	#[test]
+	fn pixel_sizes() {
+		assert_eq!(pixel_size(uvec2(800, 400)), 1.0 / 800.0);
+		assert_eq!(pixel_size(uvec2(300, 600)), 1.0 / 600.0);
+		assert_eq!(
+			pixel_size_uv(uvec2(800, 400)),
+			vec2(1.0 / 800.0, 1.0 / 400.0)
+		);
+		assert_eq!(pixel_size(UVec2::ZERO), 1.0);
+		assert_eq!(footprint(vec2(1.0, 0.0), uvec2(800, 400)), 1.0 / 800.0);
+		assert_eq!(footprint(vec2(0.0, -1.0), uvec2(800, 400)), 1.0 / 400.0);
+
+		// neighbouring pixel centers are one pixel apart in aspect preserving space
+		for size in [uvec2(800, 400), uvec2(300, 600)] {
+			let a = aspect_preserving_uv(vec2(0.5, 0.5), size);
+			let b = aspect_preserving_uv(vec2(0.5 + 1.0 / size.x as f32, 0.5), size);
+			assert!(((b - a).x - pixel_size(size)).abs() < 1e-7);
+		}
+	}
+
+	#[test]
+	fn edge_is_one_pixel_wide() {
+		for size in [uvec2(64, 64), uvec2(1920, 1080)] {
+			let px = pixel_size(size);
+			assert_eq!(coverage(0.0, size), 0.5);
+			assert_eq!(coverage(-px * 0.5, size), 1.0);
+			assert_eq!(coverage(px * 0.5, size), 0.0);
+			assert_eq!(coverage_scaled(px * 5.0, size, 10.0), 0.0);
+			assert!((coverage_scaled(px * 4.0, size, 10.0) - 0.1).abs() < 1e-6);
+		}
+	}
+
+	#[test]
+	fn coverage_matches_supersampling() {
+		// the box filter over a pixel straddling the edge of the half plane x < 0, whose
+		// distance is x, is its covered area
+		let size = uvec2(100, 100);
+		let px = pixel_size(size);
+		for i in 0..10 {
+			let x = (i as f32 / 9.0 - 0.5) * px;
+			let mut inside = 0;
+			for s in 0..1000 {
+				let sx = x + (s as f32 + 0.5) / 1000.0 * px - px * 0.5;
+				inside += (sx < 0.0) as i32;
+			}
+			let expected = inside as f32 / 1000.0;
+			assert!((coverage(x, size) - expected).abs() < 2e-3, "{x}");
+		}
+	}
+
+	#[test]
+	fn derivative_filter_widths() {
+		// a diagonal edge whose distance changes by 0.1 per pixel along both axes
+		let (dx, dy) = (0.1, 0.1);
+		assert_eq!(coverage_fwidth(0.0, dx + dy), 0.5);
+		assert_eq!(coverage_fwidth(0.1, dx + dy), 0.0);
+		assert_eq!(coverage_gradient(0.0, vec2(dx, dy)), 0.5);
+		// the gradient length is narrower than fwidth, so the same distance is covered less
+		assert_eq!(coverage_gradient(0.1, vec2(dx, dy)), 0.0);
+		assert!(coverage_gradient(0.05, vec2(dx, dy)) < coverage_fwidth(0.05, dx + dy));
+		// flat fields don't divide by zero
+		assert_eq!(coverage_fwidth(-0.1, 0.0), 1.0);
+		assert_eq!(coverage_gradient(0.1, Vec2::ZERO), 0.0);
+	}
+
+	#[test]
+	fn strokes_in_pixels() {
+		let size = uvec2(200, 100);
+		let px = pixel_size(size);
+		assert_eq!(stroke_pixels(0.0, 3.0, size), 1.0);
+		assert_eq!(stroke_pixels(1.5 * px, 3.0, size), 0.5);
+		assert_eq!(stroke_pixels(-2.0 * px, 3.0, size), 0.0);
+	}
+}
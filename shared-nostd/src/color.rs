@@ -0,0 +1,272 @@
+// Color space conversions. Hues are given in turns, [0, 1) covers the full circle.
+
+#[cfg(not(target_arch = "spirv"))]
+use glam::{Vec3, vec3};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{Vec3, vec3};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+use core::f32::consts::TAU;
+
+/// Exact sRGB transfer function, unlike the `powf(2.2)` approximation.
+pub fn srgb_to_linear(c: f32) -> f32 {
+	if c <= 0.04045 {
+		c / 12.92
+	} else {
+		((c + 0.055) / 1.055).powf(2.4)
+	}
+}
+
+pub fn linear_to_srgb(c: f32) -> f32 {
+	if c <= 0.0031308 {
+		c * 12.92
+	} else {
+		1.055 * c.powf(1.0 / 2.4) - 0.055
+	}
+}
+
+pub fn srgb_to_linear_rgb(c: Vec3) -> Vec3 {
+	vec3(
+		srgb_to_linear(c.x),
+		srgb_to_linear(c.y),
+		srgb_to_linear(c.z),
+	)
+}
+
+pub fn linear_to_srgb_rgb(c: Vec3) -> Vec3 {
+	vec3(
+		linear_to_srgb(c.x),
+		linear_to_srgb(c.y),
+		linear_to_srgb(c.z),
+	)
+}
+
+/// Linear sRGB to Oklab with Björn Ottosson's reference coefficients.
+#[allow(clippy::excessive_precision)]
+pub fn linear_to_oklab(c: Vec3) -> Vec3 {
+	let l = 0.4122214708 * c.x + 0.5363325363 * c.y + 0.0514459929 * c.z;
+	let m = 0.2119034982 * c.x + 0.6806995451 * c.y + 0.1073969566 * c.z;
+	let s = 0.0883024619 * c.x + 0.2817188376 * c.y + 0.6299787005 * c.z;
+	let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+	vec3(
+		0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+		1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+		0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+	)
+}
+
+#[allow(clippy::excessive_precision)]
+pub fn oklab_to_linear(c: Vec3) -> Vec3 {
+	let l = c.x + 0.3963377774 * c.y + 0.2158037573 * c.z;
+	let m = c.x - 0.1055613458 * c.y - 0.0638541728 * c.z;
+	let s = c.x - 0.0894841775 * c.y - 1.2914855480 * c.z;
+	let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+	vec3(
+		4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+		-1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+		-0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+	)
+}
+
+/// Oklab to lightness, chroma and hue.
+pub fn oklab_to_oklch(c: Vec3) -> Vec3 {
+	let chroma = (c.y * c.y + c.z * c.z).sqrt();
+	vec3(c.x, chroma, wrap_hue(c.z.atan2(c.y) / TAU))
+}
+
+pub fn oklch_to_oklab(c: Vec3) -> Vec3 {
+	let h = c.z * TAU;
+	vec3(c.x, c.y * h.cos(), c.y * h.sin())
+}
+
+pub fn linear_to_oklch(c: Vec3) -> Vec3 {
+	oklab_to_oklch(linear_to_oklab(c))
+}
+
+pub fn oklch_to_linear(c: Vec3) -> Vec3 {
+	oklab_to_linear(oklch_to_oklab(c))
+}
+
+/// Wraps any hue into [0, 1).
+pub fn wrap_hue(h: f32) -> f32 {
+	let h = h - h.floor();
+	// -tiny values round up to exactly 1.0
+	if h >= 1.0 { 0.0 } else { h }
+}
+
+/// Interpolates hues along the shorter way around the circle.
+pub fn lerp_hue(a: f32, b: f32, t: f32) -> f32 {
+	let mut d = wrap_hue(b - a);
+	if d > 0.5 {
+		d -= 1.0;
+	}
+	wrap_hue(a + d * t)
+}
+
+fn hue_chroma(c: Vec3) -> (f32, f32, f32, f32) {
+	let max = c.x.max(c.y).max(c.z);
+	let min = c.x.min(c.y).min(c.z);
+	let d = max - min;
+	let h = if d == 0.0 {
+		0.0
+	} else if max == c.x {
+		(c.y - c.z) / d
+	} else if max == c.y {
+		(c.z - c.x) / d + 2.0
+	} else {
+		(c.x - c.y) / d + 4.0
+	};
+	(wrap_hue(h / 6.0), d, max, min)
+}
+
+// `n + h` wrapped into [0, period), both are already in that range.
+fn hue_offset(n: f32, h: f32, period: f32) -> f32 {
+	let k = n + h;
+	if k >= period { k - period } else { k }
+}
+
+/// Hue, saturation and value, all in [0, 1].
+pub fn rgb_to_hsv(c: Vec3) -> Vec3 {
+	let (h, d, max, _) = hue_chroma(c);
+	let s = if max == 0.0 { 0.0 } else { d / max };
+	vec3(h, s, max)
+}
+
+pub fn hsv_to_rgb(c: Vec3) -> Vec3 {
+	let h = wrap_hue(c.x) * 6.0;
+	let f = |n: f32| {
+		let k = hue_offset(n, h, 6.0);
+		c.z - c.z * c.y * k.min(4.0 - k).clamp(0.0, 1.0)
+	};
+	vec3(f(5.0), f(3.0), f(1.0))
+}
+
+/// Hue, saturation and lightness, all in [0, 1].
+pub fn rgb_to_hsl(c: Vec3) -> Vec3 {
+	let (h, d, max, min) = hue_chroma(c);
+	let l = (max + min) * 0.5;
+	let s = if d == 0.0 {
+		0.0
+	} else {
+		d / (1.0 - (2.0 * l - 1.0).abs())
+	};
+	vec3(h, s, l)
+}
+
+pub fn hsl_to_rgb(c: Vec3) -> Vec3 {
+	let h = wrap_hue(c.x) * 12.0;
+	let a = c.y * c.z.min(1.0 - c.z);
+	let f = |n: f32| {
+		let k = hue_offset(n, h, 12.0);
+		c.z - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+	};
+	vec3(f(0.0), f(8.0), f(4.0))
+}
+
+/// Relative luminance of linear Rec.709 / sRGB primaries.
+pub fn luminance_rec709(c: Vec3) -> f32 {
+	c.dot(vec3(0.2126, 0.7152, 0.0722))
+}
+
+/// Relative luminance of linear Rec.2020 primaries.
+pub fn luminance_rec2020(c: Vec3) -> f32 {
+	c.dot(vec3(0.2627, 0.6780, 0.0593))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(a: Vec3, b: Vec3, eps: f32) {
+		assert!((a - b).abs().max_element() < eps, "{a} != {b}");
+	}
+
+	#[test]
+	fn srgb_transfer() {
+		assert_eq!(srgb_to_linear(0.0), 0.0);
+		assert_eq!(srgb_to_linear(1.0), 1.0);
+		assert!((srgb_to_linear(0.5) - 0.214_041_14).abs() < 1e-6);
+		assert!((linear_to_srgb(0.5) - 0.735_356_9).abs() < 1e-6);
+		// linear segment
+		assert!((srgb_to_linear(0.04) - 0.04 / 12.92).abs() < 1e-7);
+		for i in 0..=255 {
+			let c = i as f32 / 255.0;
+			assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+		}
+	}
+
+	#[test]
+	fn oklab_reference_values() {
+		assert_close(linear_to_oklab(Vec3::ONE), vec3(1.0, 0.0, 0.0), 1e-4);
+		assert_close(
+			linear_to_oklab(vec3(1.0, 0.0, 0.0)),
+			vec3(0.627_955, 0.224_863, 0.125_846),
+			1e-4,
+		);
+		assert_close(
+			linear_to_oklab(vec3(0.0, 0.0, 1.0)),
+			vec3(0.452_014, -0.032_457, -0.311_528),
+			1e-4,
+		);
+		let lch = linear_to_oklch(vec3(1.0, 0.0, 0.0));
+		assert_close(lch, vec3(0.627_955, 0.257_683, 29.2339 / 360.0), 1e-4);
+
+		let c = vec3(0.2, 0.7, 0.4);
+		assert_close(oklab_to_linear(linear_to_oklab(c)), c, 1e-5);
+		assert_close(oklch_to_linear(linear_to_oklch(c)), c, 1e-5);
+	}
+
+	#[test]
+	fn hsv_and_hsl() {
+		assert_close(rgb_to_hsv(vec3(1.0, 0.0, 0.0)), vec3(0.0, 1.0, 1.0), 1e-6);
+		assert_close(rgb_to_hsv(vec3(0.0, 0.5, 0.5)), vec3(0.5, 1.0, 0.5), 1e-6);
+		assert_close(rgb_to_hsl(vec3(1.0, 0.0, 0.0)), vec3(0.0, 1.0, 0.5), 1e-6);
+		assert_close(
+			rgb_to_hsl(vec3(0.5, 0.25, 0.75)),
+			vec3(0.75, 0.5, 0.5),
+			1e-6,
+		);
+		// magenta has a negative hue before wrapping
+		assert_close(
+			rgb_to_hsv(vec3(1.0, 0.0, 0.5)),
+			vec3(11.0 / 12.0, 1.0, 1.0),
+			1e-6,
+		);
+
+		// hues outside of [0, 1) wrap around
+		assert_close(hsv_to_rgb(vec3(1.0, 1.0, 1.0)), vec3(1.0, 0.0, 0.0), 1e-6);
+		assert_close(
+			hsl_to_rgb(vec3(-1.0 / 3.0, 1.0, 0.5)),
+			vec3(0.0, 0.0, 1.0),
+			1e-6,
+		);
+
+		for i in 0..64 {
+			let c = vec3(
+				(i % 4) as f32 / 3.0,
+				(i / 4 % 4) as f32 / 3.0,
+				(i / 16) as f32 / 3.0,
+			);
+			assert_close(hsv_to_rgb(rgb_to_hsv(c)), c, 1e-5);
+			assert_close(hsl_to_rgb(rgb_to_hsl(c)), c, 1e-5);
+		}
+	}
+
+	#[test]
+	fn hue_wrapping_and_luminance() {
+		assert_eq!(wrap_hue(1.25), 0.25);
+		assert_eq!(wrap_hue(-0.25), 0.75);
+		assert!((lerp_hue(0.9, 0.1, 0.5) - 0.0).abs() < 1e-6);
+		assert!((lerp_hue(0.1, 0.9, 0.25) - 0.05).abs() < 1e-6);
+		assert!((lerp_hue(0.2, 0.4, 0.5) - 0.3).abs() < 1e-6);
+
+		assert!((luminance_rec709(Vec3::ONE) - 1.0).abs() < 1e-6);
+		assert!((luminance_rec2020(Vec3::ONE) - 1.0).abs() < 1e-6);
+		assert!(luminance_rec709(vec3(0.0, 1.0, 0.0)) > luminance_rec709(vec3(1.0, 0.0, 0.0)));
+	}
+}
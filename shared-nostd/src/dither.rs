@@ -0,0 +1,218 @@
+// Dithering against banding when writing to low bit depth targets. The noise functions take
+// the pixel coordinate, e.g. `uv * size` or the fragment coordinate.
+
+use crate::voronoi::cell_hash;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{UVec2, Vec2, Vec3, vec2, vec3};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{UVec2, Vec2, Vec3, vec2, vec3};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+fn bayer2_index(p: UVec2) -> u32 {
+	(((p.x ^ p.y) & 1) << 1) | (p.y & 1)
+}
+
+// Each matrix doubling is 4 * M(p mod n) + M2(p / n).
+fn bayer4_index(p: UVec2) -> u32 {
+	4 * bayer2_index(p & 1u32) + bayer2_index((p >> 1u32) & 1u32)
+}
+
+fn bayer8_index(p: UVec2) -> u32 {
+	4 * bayer4_index(p & 3u32) + bayer2_index((p >> 2u32) & 1u32)
+}
+
+/// Ordered dither threshold from the 2x2 Bayer matrix, in (0, 1).
+pub fn bayer2(p: UVec2) -> f32 {
+	(bayer2_index(p) as f32 + 0.5) / 4.0
+}
+
+pub fn bayer4(p: UVec2) -> f32 {
+	(bayer4_index(p) as f32 + 0.5) / 16.0
+}
+
+pub fn bayer8(p: UVec2) -> f32 {
+	(bayer8_index(p) as f32 + 0.5) / 64.0
+}
+
+/// Jorge Jimenez's interleaved gradient noise in [0, 1), cheap and with little low
+/// frequency content.
+pub fn interleaved_gradient_noise(pixel: Vec2) -> f32 {
+	let f = pixel.dot(vec2(0.067_110_56, 0.005_837_15));
+	let f = 52.982_918 * (f - f.floor());
+	f - f.floor()
+}
+
+/// Interleaved gradient noise that changes every frame, for temporal accumulation.
+pub fn interleaved_gradient_noise_animated(pixel: Vec2, frame: u32) -> f32 {
+	interleaved_gradient_noise(pixel + 5.588_238 * (frame & 63) as f32)
+}
+
+/// Remaps a uniform value in [0, 1) to a triangular distribution in [-1, 1). Keeps the
+/// spectrum of blue noise samples, unlike summing two samples.
+pub fn triangular(u: f32) -> f32 {
+	let r = u * 2.0 - 1.0;
+	let t = 1.0 - (1.0 - r.abs()).max(0.0).sqrt();
+	if r < 0.0 { -t } else { t }
+}
+
+/// Triangular noise in (-1, 1) from the sum of two hashed values per pixel.
+pub fn triangular_hash(pixel: UVec2, seed: u32) -> f32 {
+	let h = cell_hash(pixel.as_ivec2() + (seed as i32) * 7919);
+	h.x + h.y - 1.0
+}
+
+// `bits` is clamped to 1..=31, which keeps the shift in range and the divisor above 0.
+fn levels(bits: u32) -> f32 {
+	((1u32 << bits.clamp(1, 31)) - 1) as f32
+}
+
+/// Rounds `v` in [0, 1] to the nearest value representable with `bits` bits, 1 to 31.
+pub fn quantize(v: f32, bits: u32) -> f32 {
+	let l = levels(bits);
+	(v.clamp(0.0, 1.0) * l).round() / l
+}
+
+pub fn quantize_rgb(c: Vec3, bits: u32) -> Vec3 {
+	vec3(
+		quantize(c.x, bits),
+		quantize(c.y, bits),
+		quantize(c.z, bits),
+	)
+}
+
+/// Quantizes with an ordered dither `threshold` in (0, 1), e.g. from `bayer8`.
+pub fn dither_ordered(v: f32, bits: u32, threshold: f32) -> f32 {
+	let l = levels(bits);
+	((v * l + threshold).floor() / l).clamp(0.0, 1.0)
+}
+
+/// Quantizes with triangular `noise` in (-1, 1), one step in each direction. Unlike
+/// uniform noise this also makes the error variance independent of the signal.
+pub fn dither_triangular(v: f32, bits: u32, noise: f32) -> f32 {
+	let l = levels(bits);
+	((v * l + noise).round() / l).clamp(0.0, 1.0)
+}
+
+/// `dither_triangular` for colors. All channels use the same sample, so the noise stays
+/// achromatic instead of showing as colored grain.
+pub fn dither_triangular_rgb(c: Vec3, bits: u32, noise: f32) -> Vec3 {
+	vec3(
+		dither_triangular(c.x, bits, noise),
+		dither_triangular(c.y, bits, noise),
+		dither_triangular(c.z, bits, noise),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::uvec2;
+
+	#[test]
+	fn bayer_matrices() {
+		assert_eq!(
+			[[0, 0], [1, 0], [0, 1], [1, 1]].map(|[x, y]| bayer2_index(uvec2(x, y))),
+			[0, 2, 3, 1]
+		);
+		let first_row: [u32; 4] = core::array::from_fn(|x| bayer4_index(uvec2(x as u32, 0)));
+		assert_eq!(first_row, [0, 8, 2, 10]);
+		let second_row: [u32; 4] = core::array::from_fn(|x| bayer4_index(uvec2(x as u32, 1)));
+		assert_eq!(second_row, [12, 4, 14, 6]);
+
+		// every threshold appears exactly once and the matrices repeat
+		for (n, index) in [
+			(2u32, bayer2_index as fn(UVec2) -> u32),
+			(4, bayer4_index),
+			(8, bayer8_index),
+		] {
+			let mut seen = [false; 64];
+			for y in 0..n {
+				for x in 0..n {
+					let i = index(uvec2(x, y)) as usize;
+					assert!(!seen[i]);
+					seen[i] = true;
+				}
+			}
+			assert!(seen[..(n * n) as usize].iter().all(|s| *s));
+		}
+		assert_eq!(bayer8(uvec2(3, 5)), bayer8(uvec2(11, 13)));
+		assert_eq!(bayer2(uvec2(0, 0)), 0.125);
+	}
+
+	#[test]
+	fn noise_distributions() {
+		let n = 256 * 256;
+		let (mut ign_sum, mut tri_sum, mut tri_sq, mut hash_sq) = (0.0, 0.0, 0.0, 0.0);
+		for i in 0..n {
+			let p = uvec2(i % 256, i / 256);
+			let ign = interleaved_gradient_noise(p.as_vec2());
+			assert!((0.0..1.0).contains(&ign));
+			ign_sum += ign as f64;
+
+			let t = triangular(ign);
+			assert!((-1.0..1.0).contains(&t));
+			tri_sum += t as f64;
+			tri_sq += (t * t) as f64;
+
+			let h = triangular_hash(p, 3);
+			assert!(h > -1.0 && h < 1.0);
+			hash_sq += (h * h) as f64;
+		}
+		let n = n as f64;
+		assert!((ign_sum / n - 0.5).abs() < 0.01);
+		assert!((tri_sum / n).abs() < 0.01);
+		// a triangular distribution on (-1, 1) has a variance of 1/6
+		assert!((tri_sq / n - 1.0 / 6.0).abs() < 0.01);
+		assert!((hash_sq / n - 1.0 / 6.0).abs() < 0.01);
+		assert_ne!(
+			interleaved_gradient_noise_animated(vec2(3.0, 4.0), 1),
+			interleaved_gradient_noise_animated(vec2(3.0, 4.0), 2)
+		);
+	}
+
+	#[test]
+	fn quantization() {
+		assert_eq!(quantize(0.5, 1), 1.0);
+		assert_eq!(quantize(0.49, 1), 0.0);
+		assert_eq!(quantize(1.5, 8), 1.0);
+		assert_eq!(quantize(128.0 / 255.0, 8), 128.0 / 255.0);
+		assert_eq!(quantize_rgb(Vec3::splat(0.3), 2), Vec3::splat(1.0 / 3.0));
+		assert_eq!(dither_ordered(0.0, 8, 0.99), 0.0);
+		assert_eq!(dither_ordered(1.0, 8, 0.01), 1.0);
+		assert_eq!(dither_triangular(1.0, 8, 0.9), 1.0);
+
+		// out of range bit depths are clamped instead of overflowing
+		assert_eq!(quantize(0.7, 0), quantize(0.7, 1));
+		assert_eq!(quantize(0.7, 32), quantize(0.7, 31));
+		assert!(dither_ordered(0.5, 0, 0.5).is_finite());
+	}
+
+	#[test]
+	fn dithering_preserves_the_mean() {
+		// a value between two 4 bit levels averages out to itself
+		let v = 0.37;
+		let step = 1.0 / 15.0;
+		let n = 64 * 64;
+		let (mut ordered, mut tpdf, mut rgb) = (0.0, 0.0, Vec3::ZERO);
+		for i in 0..n {
+			let p = uvec2(i % 64, i / 64);
+			ordered += dither_ordered(v, 4, bayer8(p));
+			let noise = triangular_hash(p, 0);
+			tpdf += dither_triangular(v, 4, noise);
+			let c = dither_triangular_rgb(Vec3::splat(v), 4, noise);
+			// gray stays gray
+			assert!(c.x == c.y && c.y == c.z);
+			rgb += c;
+		}
+		let n = n as f32;
+		assert!((quantize(v, 4) - v).abs() > step * 0.4);
+		assert!((ordered / n - v).abs() < step * 0.02);
+		assert!((tpdf / n - v).abs() < step * 0.05);
+		assert!((rgb / n - v).abs().max_element() < step * 0.05);
+	}
+}
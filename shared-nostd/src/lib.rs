@@ -4,11 +4,27 @@
 #![allow(unexpected_cfgs)]
 
 #[cfg(not(target_arch = "spirv"))]
-use glam::{UVec2, Vec2, vec2};
+use glam::{UVec2, Vec2, Vec4, vec2};
 #[cfg(target_arch = "spirv")]
-use spirv_std::glam::{UVec2, Vec2, vec2};
+use spirv_std::glam::{UVec2, Vec2, Vec4, vec2};
 
+pub mod aa;
+pub mod color;
+pub mod dither;
+pub mod lut;
+pub mod registry;
+pub mod sdf;
+pub mod sdf_ops;
 pub mod shapes;
+pub mod shaping;
+pub mod tiling;
+pub mod tonemap;
+pub mod uv;
+pub mod voronoi;
+
+/// Signature of fragment shaders that only take `uv`, `size` and `time`, which lets the host
+/// evaluate them on the CPU.
+pub type ShaderFn = fn(Vec2, UVec2, f32) -> Vec4;
 
 pub fn aspect_preserving_uv(uv: Vec2, size: UVec2) -> Vec2 {
 	let aspect = size.x as f32 / size.y as f32;
@@ -22,3 +38,38 @@ pub fn aspect_preserving_uv(uv: Vec2, size: UVec2) -> Vec2 {
 pub fn flip_y(uv: Vec2) -> Vec2 {
 	vec2(uv.x, 1.0 - uv.y)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::uvec2;
+
+	#[test]
+	fn aspect_preserving_uv_keeps_pixels_square() {
+		for size in [uvec2(800, 400), uvec2(400, 800), uvec2(300, 300)] {
+			// one pixel step in x and y covers the same distance
+			let origin = aspect_preserving_uv(Vec2::ZERO, size);
+			let dx = aspect_preserving_uv(vec2(1.0 / size.x as f32, 0.0), size) - origin;
+			let dy = aspect_preserving_uv(vec2(0.0, 1.0 / size.y as f32), size) - origin;
+			assert!((dx.x - dy.y).abs() < 1e-7, "{size}");
+			assert_eq!(origin, Vec2::ZERO);
+		}
+		// the longer side spans one unit
+		assert_eq!(
+			aspect_preserving_uv(Vec2::ONE, uvec2(800, 400)),
+			vec2(1.0, 0.5)
+		);
+		assert_eq!(
+			aspect_preserving_uv(Vec2::ONE, uvec2(400, 800)),
+			vec2(0.5, 1.0)
+		);
+		assert_eq!(aspect_preserving_uv(Vec2::ONE, uvec2(300, 300)), Vec2::ONE);
+	}
+
+	#[test]
+	fn flip_y_mirrors_vertically() {
+		assert_eq!(flip_y(vec2(0.25, 0.0)), vec2(0.25, 1.0));
+		assert_eq!(flip_y(vec2(0.25, 0.75)), vec2(0.25, 0.25));
+		assert_eq!(flip_y(flip_y(vec2(0.3, 0.6))), vec2(0.3, 0.6));
+	}
+}
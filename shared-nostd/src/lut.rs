@@ -0,0 +1,46 @@
+#[cfg(not(target_arch = "spirv"))]
+use glam::{Vec2, Vec3, Vec4, vec2};
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{Vec2, Vec3, Vec4, vec2};
+
+/// Texture coordinates of a color inside a 3D LUT that is unwrapped into a 2D strip of
+/// `size` slices, each `size` x `size` texels, laid out left to right along blue.
+/// Returns the uv in the lower and the upper blue slice and the blend factor between them.
+/// Red and green are interpolated by the bilinear sampler.
+pub fn lut_strip_uv(color: Vec3, size: u32) -> (Vec2, Vec2, f32) {
+	let n = size as f32;
+	let c = color.clamp(Vec3::ZERO, Vec3::ONE) * (n - 1.0);
+
+	// c is positive, so the cast truncates like floor
+	let slice = ((c.z as u32) as f32).min(n - 2.0).max(0.0);
+	let blend = c.z - slice;
+
+	let width = n * n;
+	let uv = vec2((c.x + 0.5) / width, (c.y + 0.5) / n);
+	let lower = uv + vec2(slice * n / width, 0.0);
+	let upper = lower + vec2(n / width, 0.0);
+	(lower, upper, blend)
+}
+
+/// Applies a 3D LUT strip with trilinear interpolation. `sample` reads the strip texture
+/// with a linear filtering sampler, e.g. `|uv| lut.sample(*sampler, uv)`.
+/// `color` is expected in the LUT domain, normalized to [0, 1].
+pub fn apply_lut(color: Vec3, size: u32, sample: impl Fn(Vec2) -> Vec4) -> Vec3 {
+	let (lower, upper, blend) = lut_strip_uv(color, size);
+	sample(lower).lerp(sample(upper), blend).truncate()
+}
+
+/// Like `apply_lut`, but first maps `color` from the LUT input domain to [0, 1].
+pub fn apply_lut_domain(
+	color: Vec3,
+	size: u32,
+	domain_min: Vec3,
+	domain_max: Vec3,
+	sample: impl Fn(Vec2) -> Vec4,
+) -> Vec3 {
+	apply_lut(
+		(color - domain_min) / (domain_max - domain_min),
+		size,
+		sample,
+	)
+}
@@ -0,0 +1,98 @@
+// Description of the fragment entry points of a shader crate, so hosts can create their
+// canvases from one table instead of wiring every shader by hand.
+
+use crate::ShaderFn;
+
+/// Uniforms and textures an entry point binds, in binding order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bindings {
+	/// `size: UVec2` at binding 0 and `time: f32` at binding 1.
+	SizeTime,
+	/// `size: UVec2` and a sampler at bindings 0 and 1, a texture layer in set 1.
+	SizeTexture,
+}
+
+impl Bindings {
+	pub fn names(self) -> &'static [&'static str] {
+		match self {
+			Bindings::SizeTime => &["size", "time"],
+			Bindings::SizeTexture => &["size", "sampler", "texture"],
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+pub struct ShaderEntry {
+	/// Name of the entry point, the host loads it from `out/<name>.spv`.
+	pub name: &'static str,
+	pub bindings: Bindings,
+	/// Whether the output changes over time and has to be redrawn every frame.
+	pub animated: bool,
+	/// The shader as plain function, for entry points that can be evaluated on the CPU.
+	pub cpu: Option<ShaderFn>,
+}
+
+/// Creates `pub const ENTRIES: &[ShaderEntry]` from an entry table in the form
+/// `name: bindings, animated;`. Every `SizeTime` entry point is called directly for the CPU
+/// version, so the table has to be expanded next to the entry points.
+#[macro_export]
+macro_rules! shader_entries {
+	($($name:ident: $bindings:ident, $animated:literal;)*) => {
+		pub const ENTRIES: &[$crate::registry::ShaderEntry] = &[$(
+			$crate::registry::ShaderEntry {
+				name: stringify!($name),
+				bindings: $crate::registry::Bindings::$bindings,
+				animated: $animated,
+				cpu: $crate::shader_entries!(@cpu $name, $bindings),
+			}
+		),*];
+	};
+	(@cpu $name:ident, SizeTime) => {
+		Some(|uv, size, time| {
+			let mut out = Default::default();
+			$name(uv, &size, &time, &mut out);
+			out
+		})
+	};
+	(@cpu $name:ident, $bindings:ident) => {
+		None
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::{UVec2, Vec2, Vec4, uvec2, vec2, vec4};
+
+	fn gradient(uv: Vec2, size: &UVec2, time: &f32, out: &mut Vec4) {
+		*out = vec4(uv.x, uv.y, *time, size.x as f32);
+	}
+
+	// `prefilled` has no function, texture entry points are never called on the CPU
+	shader_entries! {
+		gradient: SizeTime, true;
+		prefilled: SizeTexture, false;
+	}
+
+	#[test]
+	fn entries_from_table() {
+		assert_eq!(ENTRIES.len(), 2);
+		let [gradient, prefilled] = ENTRIES else {
+			unreachable!()
+		};
+
+		assert_eq!(gradient.name, "gradient");
+		assert_eq!(gradient.bindings, Bindings::SizeTime);
+		assert!(gradient.animated);
+		let cpu = gradient.cpu.expect("SizeTime entries run on the CPU");
+		assert_eq!(
+			cpu(vec2(0.5, 0.25), uvec2(4, 2), 2.0),
+			vec4(0.5, 0.25, 2.0, 4.0)
+		);
+
+		assert_eq!(prefilled.name, "prefilled");
+		assert_eq!(prefilled.bindings.names(), ["size", "sampler", "texture"]);
+		assert!(!prefilled.animated);
+		assert!(prefilled.cpu.is_none());
+	}
+}
@@ -0,0 +1,339 @@
+// Signed distance functions for 2D shapes, negative inside. Shapes are centered at the
+// origin, translate the sample point to place them. Most are ports of Inigo Quilez'
+// https://iquilezles.org/articles/distfunctions2d/
+
+use core::f32::consts::PI;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{Vec2, Vec4, Vec4Swizzles, vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{Vec2, Vec4, Vec4Swizzles, vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+use trivalibs_nostd::prelude::*;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+pub fn circle(p: Vec2, radius: f32) -> f32 {
+	p.length() - radius
+}
+
+pub fn rect(p: Vec2, half_size: Vec2) -> f32 {
+	let d = p.abs() - half_size;
+	d.max(Vec2::ZERO).length() + d.x.max(d.y).min(0.0)
+}
+
+/// Rect with a radius per corner: `x` for the +x +y corner, `y` for +x -y, `z` for -x +y
+/// and `w` for -x -y.
+pub fn rounded_rect(p: Vec2, half_size: Vec2, radii: Vec4) -> f32 {
+	let r = if p.x > 0.0 { radii.xy() } else { radii.zw() };
+	let r = if p.y > 0.0 { r.x } else { r.y };
+	let q = p.abs() - half_size + r;
+	q.x.max(q.y).min(0.0) + q.max(Vec2::ZERO).length() - r
+}
+
+/// Unsigned distance to the line segment from `a` to `b`.
+pub fn segment(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+	let pa = p - a;
+	let ba = b - a;
+	let h = (pa.dot(ba) / ba.dot(ba)).clamp(0.0, 1.0);
+	(pa - ba * h).length()
+}
+
+pub fn triangle(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> f32 {
+	let (e0, e1, e2) = (b - a, c - b, a - c);
+	let (v0, v1, v2) = (p - a, p - b, p - c);
+	let pq0 = v0 - e0 * (v0.dot(e0) / e0.dot(e0)).clamp(0.0, 1.0);
+	let pq1 = v1 - e1 * (v1.dot(e1) / e1.dot(e1)).clamp(0.0, 1.0);
+	let pq2 = v2 - e2 * (v2.dot(e2) / e2.dot(e2)).clamp(0.0, 1.0);
+	// winding of the triangle
+	let s = e0.perp_dot(e2).signum();
+	let d = vec2(pq0.dot(pq0), s * v0.perp_dot(e0))
+		.min(vec2(pq1.dot(pq1), s * v1.perp_dot(e1)))
+		.min(vec2(pq2.dot(pq2), s * v2.perp_dot(e2)));
+	-d.x.sqrt() * d.y.signum()
+}
+
+/// Closed polygon through all `vertices`, which may be concave but must not intersect.
+pub fn polygon<const N: usize>(p: Vec2, vertices: &[Vec2; N]) -> f32 {
+	let mut d = (p - vertices[0]).length_squared();
+	let mut s = 1.0;
+	let mut j = N - 1;
+	// index loop instead of iterator adapters, which are poorly supported on the GPU
+	#[allow(clippy::needless_range_loop)]
+	for i in 0..N {
+		let vi = vertices[i];
+		let vj = vertices[j];
+		let e = vj - vi;
+		let w = p - vi;
+		let b = w - e * (w.dot(e) / e.dot(e)).clamp(0.0, 1.0);
+		d = d.min(b.length_squared());
+
+		// crossing count along +x decides inside or outside
+		let c0 = p.y >= vi.y;
+		let c1 = p.y < vj.y;
+		let c2 = e.x * w.y > e.y * w.x;
+		if (c0 && c1 && c2) || (!c0 && !c1 && !c2) {
+			s = -s;
+		}
+		j = i;
+	}
+	s * d.sqrt()
+}
+
+/// Axis aligned ellipse with the given semi-axes. Iterates towards the closest point on
+/// the outline, which stays stable on the axes where the analytic solution breaks down.
+pub fn ellipse(p: Vec2, radii: Vec2) -> f32 {
+	let p = p.abs();
+	let ab2 = radii * radii;
+	let mut t = Vec2::splat(core::f32::consts::FRAC_1_SQRT_2);
+	for _ in 0..4 {
+		let x = radii * t;
+		// center of curvature of the outline at x
+		let e = vec2(ab2.x - ab2.y, ab2.y - ab2.x) * t * t * t / radii;
+		let r = (x - e).length();
+		let q = (p - e).length().max(1e-6);
+		t = (((p - e) * r / q + e) / radii).clamp(Vec2::ZERO, Vec2::ONE);
+		t /= t.length().max(1e-6);
+	}
+	let closest = radii * t;
+	let inside = (p / radii).length_squared() - 1.0;
+	(closest - p).length() * inside.signum()
+}
+
+/// Arc of a circle with `radius`, symmetric around +y and spanning `aperture` radians to
+/// each side. `thickness` is the distance from the center line to the edge.
+pub fn arc(p: Vec2, aperture: f32, radius: f32, thickness: f32) -> f32 {
+	let sc = vec2(aperture.sin(), aperture.cos());
+	let p = vec2(p.x.abs(), p.y);
+	let d = if sc.y * p.x > sc.x * p.y {
+		(p - sc * radius).length()
+	} else {
+		(p.length() - radius).abs()
+	};
+	d - thickness
+}
+
+/// Star with `points` tips at `radius`. `sharpness` between 2 and `points` controls
+/// how deep the inner corners are, 2 gives a regular polygon.
+pub fn star(p: Vec2, radius: f32, points: u32, sharpness: f32) -> f32 {
+	let an = PI / points as f32;
+	let en = PI / sharpness;
+	let acs = vec2(an.cos(), an.sin());
+	let ecs = vec2(en.cos(), en.sin());
+
+	let angle = p.x.atan2(p.y);
+	let period = 2.0 * an;
+	let bn = angle - period * (angle / period).floor() - an;
+
+	let mut p = p.length() * vec2(bn.cos(), bn.sin().abs());
+	p -= radius * acs;
+	p += ecs * (-p.dot(ecs)).clamp(0.0, radius * acs.y / ecs.y);
+	p.length() * p.x.signum()
+}
+
+/// Regular hexagon with flat top and bottom, `radius` is the inner radius.
+pub fn hexagon(p: Vec2, radius: f32) -> f32 {
+	let k = vec2(-0.866_025_4, 0.5);
+	let kz = 0.577_350_26;
+	let mut p = p.abs();
+	p -= 2.0 * k.dot(p).min(0.0) * k;
+	p -= vec2(p.x.clamp(-kz * radius, kz * radius), radius);
+	p.length() * p.y.signum()
+}
+
+/// Unsigned distance to the quadratic bezier curve from `a` to `c` with control point `b`.
+pub fn quadratic_bezier(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> f32 {
+	let qa = b - a;
+	let qb = a - 2.0 * b + c;
+	if qb.length_squared() < 1e-8 {
+		return segment(p, a, c);
+	}
+	let qc = qa * 2.0;
+	let qd = a - p;
+
+	let kk = 1.0 / qb.dot(qb);
+	let kx = kk * qa.dot(qb);
+	let ky = kk * (2.0 * qa.dot(qa) + qd.dot(qb)) / 3.0;
+	let kz = kk * qd.dot(qa);
+
+	let pp = ky - kx * kx;
+	let p3 = pp * pp * pp;
+	let q = kx * (2.0 * kx * kx - 3.0 * ky) + kz;
+	let h = q * q + 4.0 * p3;
+
+	let dist2 = |t: f32| (qd + (qc + qb * t) * t).length_squared();
+	let res = if h >= 0.0 {
+		let h = h.sqrt();
+		let x = (vec2(h, -h) - q) / 2.0;
+		let uv = vec2(
+			x.x.signum() * x.x.abs().cbrt(),
+			x.y.signum() * x.y.abs().cbrt(),
+		);
+		dist2((uv.x + uv.y - kx).clamp(0.0, 1.0))
+	} else {
+		let z = (-pp).sqrt();
+		let v = (q / (pp * z * 2.0)).acos() / 3.0;
+		let m = v.cos();
+		let n = v.sin() * SQRT_3;
+		// the third root can not be the closest one
+		let t0 = ((m + m) * z - kx).clamp(0.0, 1.0);
+		let t1 = ((-n - m) * z - kx).clamp(0.0, 1.0);
+		dist2(t0).min(dist2(t1))
+	};
+	res.sqrt()
+}
+
+/// Intersection of two circles with `radius` whose centers are `offset` left and right
+/// of the origin. The result is a vertical lens shape.
+pub fn vesica(p: Vec2, radius: f32, offset: f32) -> f32 {
+	let p = p.abs();
+	let b = (radius * radius - offset * offset).sqrt();
+	if (p.y - b) * offset > p.x * b {
+		(p - vec2(0.0, b)).length()
+	} else {
+		(p - vec2(-offset, 0.0)).length() - radius
+	}
+}
+
+/// Coverage of a shape from its signed distance, 1.0 inside and 0.0 outside, with a
+/// linear ramp of `aa` width centered on the edge. Use roughly one pixel in distance units.
+pub fn fill(d: f32, aa: f32) -> f32 {
+	(0.5 - d / aa).clamp(0.0, 1.0)
+}
+
+/// Like `fill`, but with a smoothstep ramp.
+pub fn fill_smooth(d: f32, aa: f32) -> f32 {
+	d.smoothstep(aa * 0.5, -aa * 0.5)
+}
+
+/// Coverage of the outline of a shape with the given stroke `width`.
+pub fn stroke(d: f32, width: f32, aa: f32) -> f32 {
+	fill(d.abs() - width * 0.5, aa)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(a: f32, b: f32) {
+		assert!((a - b).abs() < 1e-4, "{a} != {b}");
+	}
+
+	// A correct distance field never changes faster than the distance between points.
+	fn assert_lipschitz(f: impl Fn(Vec2) -> f32) {
+		let step = 0.05;
+		for y in -40..40 {
+			for x in -40..40 {
+				let p = vec2(x as f32, y as f32) * step;
+				let d = f(p);
+				assert!(d.is_finite(), "{p}");
+				assert!((f(p + vec2(step, 0.0)) - d).abs() <= step * 1.001, "{p}");
+				assert!((f(p + vec2(0.0, step)) - d).abs() <= step * 1.001, "{p}");
+			}
+		}
+	}
+
+	#[test]
+	fn known_distances() {
+		assert_close(circle(vec2(3.0, 4.0), 1.0), 4.0);
+		assert_close(rect(vec2(3.0, 0.0), vec2(1.0, 1.0)), 2.0);
+		assert_close(rect(Vec2::ZERO, vec2(1.0, 2.0)), -1.0);
+		assert_close(segment(vec2(0.5, 1.0), Vec2::ZERO, vec2(1.0, 0.0)), 1.0);
+		assert_close(hexagon(vec2(0.0, 2.0), 1.0), 1.0);
+		assert_close(hexagon(Vec2::ZERO, 1.0), -1.0);
+		assert_close(ellipse(vec2(3.0, 0.0), vec2(2.0, 1.0)), 1.0);
+		assert_close(ellipse(vec2(0.0, 3.0), vec2(2.0, 1.0)), 2.0);
+		assert_close(ellipse(Vec2::ZERO, vec2(2.0, 1.0)), -1.0);
+		assert_close(arc(vec2(0.0, 2.0), 0.5, 1.0, 0.1), 0.9);
+		assert_close(
+			arc(vec2(0.0, -1.0), 0.5, 1.0, 0.1),
+			2.0 * 0.25f32.cos() - 0.1,
+		);
+		assert_close(vesica(vec2(0.0, 0.0), 1.0, 0.5), -0.5);
+		assert_close(star(vec2(0.0, 2.0), 1.0, 5, 2.0), 1.0);
+		assert_close(
+			quadratic_bezier(vec2(0.0, 1.0), vec2(-1.0, 0.0), Vec2::ZERO, vec2(1.0, 0.0)),
+			1.0,
+		);
+		let (a, b, c) = (vec2(-1.0, 0.0), vec2(0.0, 2.0), vec2(1.0, 0.0));
+		assert_close(quadratic_bezier(vec2(0.0, 1.0), a, b, c), 0.0);
+		assert_close(quadratic_bezier(vec2(0.0, 2.0), a, b, c), 1.0);
+	}
+
+	#[test]
+	fn ellipse_matches_sampled_outline() {
+		let radii = vec2(1.5, 0.6);
+		let outline: [Vec2; 2000] = core::array::from_fn(|i| {
+			let a = i as f32 / 2000.0 * core::f32::consts::TAU;
+			radii * vec2(a.cos(), a.sin())
+		});
+		for y in -10..10 {
+			for x in -10..10 {
+				let p = vec2(x as f32, y as f32) * 0.21;
+				let d = outline
+					.iter()
+					.map(|o| (*o - p).length())
+					.fold(f32::MAX, f32::min);
+				assert!((ellipse(p, radii).abs() - d).abs() < 2e-3, "{p}");
+			}
+		}
+	}
+
+	#[test]
+	fn polygons_match_primitives() {
+		let (a, b, c) = (vec2(-1.0, -0.5), vec2(1.2, -0.7), vec2(0.1, 1.1));
+		let square = [
+			vec2(-1.0, -1.0),
+			vec2(1.0, -1.0),
+			vec2(1.0, 1.0),
+			vec2(-1.0, 1.0),
+		];
+		for y in -20..20 {
+			for x in -20..20 {
+				let p = vec2(x as f32, y as f32) * 0.13;
+				assert_close(triangle(p, a, b, c), polygon(p, &[a, b, c]));
+				assert_close(triangle(p, a, c, b), polygon(p, &[a, b, c]));
+				assert_close(polygon(p, &square), rect(p, Vec2::ONE));
+				assert_close(rounded_rect(p, Vec2::ONE, Vec4::ZERO), rect(p, Vec2::ONE));
+			}
+		}
+	}
+
+	#[test]
+	fn distances_are_lipschitz() {
+		assert_lipschitz(|p| rounded_rect(p, vec2(1.0, 0.6), Vec4::new(0.1, 0.5, 0.0, 0.3)));
+		assert_lipschitz(|p| ellipse(p, vec2(1.5, 0.7)));
+		assert_lipschitz(|p| arc(p, 2.0, 1.0, 0.2));
+		assert_lipschitz(|p| star(p, 1.2, 6, 3.0));
+		assert_lipschitz(|p| hexagon(p, 0.8));
+		assert_lipschitz(|p| vesica(p, 1.0, 0.6));
+		assert_lipschitz(|p| quadratic_bezier(p, vec2(-1.0, -1.0), vec2(0.0, 2.0), vec2(1.5, -0.5)));
+		assert_lipschitz(|p| {
+			polygon(
+				p,
+				&[
+					vec2(-1.0, -1.0),
+					vec2(1.0, -1.0),
+					vec2(0.0, 0.0),
+					vec2(1.0, 1.0),
+					vec2(-1.0, 1.0),
+				],
+			)
+		});
+	}
+
+	#[test]
+	fn coverage() {
+		assert_eq!(fill(-1.0, 1.0), 1.0);
+		assert_eq!(fill(0.0, 1.0), 0.5);
+		assert_eq!(fill(1.0, 1.0), 0.0);
+		assert_eq!(fill_smooth(0.0, 1.0), 0.5);
+		assert_eq!(fill_smooth(-0.5, 1.0), 1.0);
+		assert_eq!(stroke(0.0, 0.2, 0.01), 1.0);
+		assert_eq!(stroke(-0.5, 0.2, 0.01), 0.0);
+	}
+}
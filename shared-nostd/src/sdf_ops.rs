@@ -0,0 +1,215 @@
+// Operators to combine distances from `sdf` and to transform the sample point before a
+// distance function is evaluated.
+
+use core::f32::consts::TAU;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{Vec2, vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{Vec2, vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+pub fn union(a: f32, b: f32) -> f32 {
+	a.min(b)
+}
+
+/// Cuts `b` out of `a`.
+pub fn subtract(a: f32, b: f32) -> f32 {
+	a.max(-b)
+}
+
+pub fn intersect(a: f32, b: f32) -> f32 {
+	a.max(b)
+}
+
+/// Quadratic smooth minimum, blends over a distance of `k`.
+pub fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+	let h = (k - (a - b).abs()).max(0.0) / k;
+	a.min(b) - h * h * k * 0.25
+}
+
+pub fn smooth_max(a: f32, b: f32, k: f32) -> f32 {
+	-smooth_min(-a, -b, k)
+}
+
+pub fn smooth_union(a: f32, b: f32, k: f32) -> f32 {
+	smooth_min(a, b, k)
+}
+
+pub fn smooth_subtract(a: f32, b: f32, k: f32) -> f32 {
+	smooth_max(a, -b, k)
+}
+
+pub fn smooth_intersect(a: f32, b: f32, k: f32) -> f32 {
+	smooth_max(a, b, k)
+}
+
+/// Grows the shape by `radius`, which rounds its corners.
+pub fn round(d: f32, radius: f32) -> f32 {
+	d - radius
+}
+
+/// Ring of `thickness` around the outline of the shape.
+pub fn annular(d: f32, thickness: f32) -> f32 {
+	d.abs() - thickness
+}
+
+/// Applies `annular` `layers` times with halved thickness each time, which gives
+/// `2^layers` concentric rings.
+pub fn onion(d: f32, thickness: f32, layers: u32) -> f32 {
+	let mut d = d;
+	let mut t = thickness;
+	for _ in 0..layers {
+		d = annular(d, t);
+		t *= 0.5;
+	}
+	d
+}
+
+fn round_vec(v: Vec2) -> Vec2 {
+	vec2(v.x.round(), v.y.round())
+}
+
+/// Index of the repetition cell around `p`, cell 0 is centered on the origin.
+pub fn repeat_cell(p: Vec2, period: Vec2) -> Vec2 {
+	round_vec(p / period)
+}
+
+/// Infinite repetition. The returned point is local to the cell and centered on the
+/// origin. Distances stay exact as long as the shape fits into one cell.
+pub fn repeat(p: Vec2, period: Vec2) -> Vec2 {
+	p - period * repeat_cell(p, period)
+}
+
+/// Repetition limited to the cells from `-limit` to `limit`, the outer cells extend to
+/// infinity so distances outside of the grid stay correct.
+pub fn repeat_limited(p: Vec2, period: Vec2, limit: Vec2) -> Vec2 {
+	p - period * repeat_cell(p, period).clamp(-limit, limit)
+}
+
+/// Mirrors the negative x half onto the positive one.
+pub fn mirror_x(p: Vec2) -> Vec2 {
+	vec2(p.x.abs(), p.y)
+}
+
+pub fn mirror_y(p: Vec2) -> Vec2 {
+	vec2(p.x, p.y.abs())
+}
+
+/// Mirrors at the line through the origin with the unit `normal`, the half the normal
+/// points to is kept.
+pub fn mirror(p: Vec2, normal: Vec2) -> Vec2 {
+	p - 2.0 * p.dot(normal).min(0.0) * normal
+}
+
+/// Folds the plane into `count` equal angular segments around the origin. The returned
+/// point lies in the segment centered on the +x axis.
+pub fn repeat_polar(p: Vec2, count: u32) -> Vec2 {
+	let segment = TAU / count as f32;
+	let a = p.y.atan2(p.x) + segment * 0.5;
+	let a = a - segment * (a / segment).floor() - segment * 0.5;
+	p.length() * vec2(a.cos(), a.sin())
+}
+
+/// Index of the polar segment around `p`, from 0 to `count - 1` counter clockwise from +x.
+pub fn repeat_polar_index(p: Vec2, count: u32) -> u32 {
+	let segment = TAU / count as f32;
+	let a = p.y.atan2(p.x) + segment * 0.5;
+	let i = (a / segment).floor() as i32;
+	i.rem_euclid(count as i32) as u32
+}
+
+/// Transforms the sample point so that the shape appears rotated by `angle` radians
+/// counter clockwise.
+pub fn rotate(p: Vec2, angle: f32) -> Vec2 {
+	let (s, c) = angle.sin_cos();
+	vec2(c * p.x + s * p.y, -s * p.x + c * p.y)
+}
+
+/// Evaluates `sdf` on a shape scaled uniformly by `factor` and keeps distances exact.
+pub fn scale(p: Vec2, factor: f32, sdf: impl Fn(Vec2) -> f32) -> f32 {
+	sdf(p / factor) * factor
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::sdf;
+
+	fn assert_close(a: f32, b: f32) {
+		assert!((a - b).abs() < 1e-4, "{a} != {b}");
+	}
+
+	fn assert_vec_close(a: Vec2, b: Vec2) {
+		assert!((a - b).abs().max_element() < 1e-4, "{a} != {b}");
+	}
+
+	#[test]
+	fn boolean_operators() {
+		let a = sdf::circle(vec2(0.5, 0.0), 1.0);
+		let b = sdf::circle(vec2(1.5, 0.0), 1.0);
+		assert_close(union(a, b), -0.5);
+		assert_close(intersect(a, b), 0.5);
+		assert_close(subtract(a, b), -0.5);
+		assert_close(subtract(b, a), 0.5);
+		assert!(smooth_union(a, b, 0.5) <= union(a, b));
+		assert!(smooth_intersect(a, b, 0.5) >= intersect(a, b));
+		// far apart distances are not affected by the blend
+		assert_close(smooth_union(1.0, 3.0, 0.5), 1.0);
+		assert_close(smooth_subtract(1.0, -3.0, 0.5), 3.0);
+		assert_close(annular(0.5, 0.1), 0.4);
+		assert_close(round(0.5, 0.1), 0.4);
+		assert_close(onion(-1.0, 0.2, 1), annular(-1.0, 0.2));
+		assert_close(onion(0.0, 0.4, 2), 0.2);
+	}
+
+	#[test]
+	fn domain_repetition() {
+		let period = vec2(2.0, 3.0);
+		assert_vec_close(repeat(vec2(4.5, -3.5), period), vec2(0.5, -0.5));
+		assert_eq!(repeat_cell(vec2(4.5, -3.5), period), vec2(2.0, -1.0));
+		let limit = Vec2::ONE;
+		assert_vec_close(
+			repeat_limited(vec2(4.5, -3.5), period, limit),
+			vec2(2.5, -0.5),
+		);
+		assert_vec_close(
+			repeat_limited(vec2(0.6, 0.2), period, limit),
+			vec2(0.6, 0.2),
+		);
+	}
+
+	#[test]
+	fn mirror_and_polar() {
+		assert_vec_close(mirror_x(vec2(-1.0, 2.0)), vec2(1.0, 2.0));
+		assert_vec_close(mirror_y(vec2(-1.0, -2.0)), vec2(-1.0, 2.0));
+		assert_vec_close(mirror(vec2(-1.0, 2.0), vec2(1.0, 0.0)), vec2(1.0, 2.0));
+		assert_vec_close(mirror(vec2(1.0, 2.0), vec2(1.0, 0.0)), vec2(1.0, 2.0));
+
+		// a point on +y with four segments lands on +x
+		assert_vec_close(repeat_polar(vec2(0.0, 2.0), 4), vec2(2.0, 0.0));
+		assert_vec_close(repeat_polar(vec2(1.0, 0.1), 4), vec2(1.0, 0.1));
+		assert_eq!(repeat_polar_index(vec2(1.0, 0.1), 4), 0);
+		assert_eq!(repeat_polar_index(vec2(0.0, 2.0), 4), 1);
+		assert_eq!(repeat_polar_index(vec2(0.1, -1.0), 4), 3);
+	}
+
+	#[test]
+	fn transforms_keep_distances() {
+		let half = vec2(1.0, 0.5);
+		let p = vec2(0.0, 2.0);
+		// a wide rect rotated by 90 degrees is tall
+		assert_close(
+			sdf::rect(rotate(p, core::f32::consts::FRAC_PI_2), half),
+			1.0,
+		);
+		assert_close(scale(p, 2.0, |p| sdf::rect(p, half)), 1.0);
+		assert_close(scale(p, 2.0, |p| sdf::circle(p, 0.5)), sdf::circle(p, 1.0));
+		let rotated = rotate(vec2(1.0, 0.0), core::f32::consts::FRAC_PI_2);
+		assert_vec_close(rotated, vec2(0.0, -1.0));
+	}
+}
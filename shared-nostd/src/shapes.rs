@@ -65,3 +65,100 @@ pub fn rounded_rect_smooth(
 
 	d.smoothstep(e0, e1)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::vec2;
+
+	const CENTER: Vec2 = Vec2::new(0.5, 0.5);
+
+	#[test]
+	fn step_shapes_boundaries() {
+		let size = vec2(0.4, 0.2);
+		assert_eq!(rect(size, CENTER, CENTER), 1.0);
+		// the edges belong to the shape
+		assert_eq!(rect(size, CENTER, vec2(0.7, 0.5)), 1.0);
+		assert_eq!(rect(size, CENTER, vec2(0.71, 0.5)), 0.0);
+		assert_eq!(rect(size, CENTER, vec2(0.5, 0.39)), 0.0);
+
+		assert_eq!(circle(CENTER, 0.25, vec2(0.75, 0.5)), 1.0);
+		assert_eq!(circle(CENTER, 0.25, vec2(0.76, 0.5)), 0.0);
+		assert_eq!(circle(CENTER, 0.25, vec2(0.67, 0.67)), 1.0);
+		assert_eq!(circle(CENTER, 0.25, vec2(0.68, 0.68)), 0.0);
+
+		// the corners are cut by the radius
+		let size = Vec2::splat(0.4);
+		assert_eq!(rounded_rect(vec2(0.69, 0.5), CENTER, size, 0.1), 1.0);
+		assert_eq!(rounded_rect(vec2(0.69, 0.69), CENTER, size, 0.1), 0.0);
+		assert_eq!(rounded_rect(vec2(0.66, 0.66), CENTER, size, 0.1), 1.0);
+	}
+
+	#[test]
+	fn shapes_are_symmetric() {
+		let size = vec2(0.5, 0.3);
+		for i in 0..20 {
+			for j in 0..20 {
+				let d = vec2(i as f32, j as f32) / 40.0;
+				let mirrored = [d, vec2(-d.x, d.y), vec2(d.x, -d.y), -d];
+				let values = mirrored.map(|m| {
+					let st = CENTER + m;
+					[
+						rect(size, CENTER, st),
+						rect_smooth(size, CENTER, st, 0.05),
+						circle(CENTER, 0.3, st),
+						circle_smooth(CENTER, 0.3, st, 0.05),
+						rounded_rect(st, CENTER, size, 0.1),
+						rounded_rect_smooth(st, CENTER, size, 0.1, 0.05),
+					]
+				});
+				for v in &values[1..] {
+					for k in 0..6 {
+						assert!((v[k] - values[0][k]).abs() < 1e-5, "{k} {d}");
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn smooth_agrees_with_step_away_from_edges() {
+		let size = vec2(0.5, 0.3);
+		for i in 0..=50 {
+			for j in 0..=50 {
+				let st = vec2(i as f32, j as f32) / 50.0;
+
+				let r = (CENTER - st).abs() / (size * 0.5);
+				if (r.max_element() - 1.0).abs() > 0.25 {
+					assert_eq!(rect_smooth(size, CENTER, st, 0.05), rect(size, CENTER, st));
+				}
+
+				// unlike the other smooth shapes, circle_smooth is 0 inside
+				let d = (st - CENTER).length();
+				if (d - 0.3).abs() > 0.05 {
+					assert_eq!(
+						circle_smooth(CENTER, 0.3, st, 0.05),
+						1.0 - circle(CENTER, 0.3, st)
+					);
+				}
+
+				let offset = size / 2.0 - 0.1;
+				let d = ((st - CENTER).abs() - offset).max(Vec2::ZERO).length();
+				if (d - 0.1).abs() > 0.025 {
+					assert_eq!(
+						rounded_rect_smooth(st, CENTER, size, 0.1, 0.05),
+						rounded_rect(st, CENTER, size, 0.1)
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn smooth_edges_are_centered() {
+		let size = vec2(0.5, 0.3);
+		assert!((rect_smooth(size, CENTER, vec2(0.75, 0.5), 0.05) - 0.5).abs() < 1e-5);
+		assert!((circle_smooth(CENTER, 0.3, vec2(0.8, 0.5), 0.05) - 0.5).abs() < 1e-5);
+		assert!((rounded_rect_smooth(vec2(0.75, 0.5), CENTER, size, 0.1, 0.05) - 0.5).abs() < 1e-5);
+	}
+}
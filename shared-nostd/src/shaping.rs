@@ -0,0 +1,369 @@
+// Easing, shaping and interpolation curves. Unless noted otherwise they take `t` in [0, 1]
+// and map 0 to 0 and 1 to 1.
+
+use core::f32::consts::{PI, TAU};
+use core::ops::{Add, Mul, Sub};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+/// Fifth order smoothstep with zero first and second derivatives at both edges.
+pub fn smootherstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+	let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+	t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+// Penner easings
+
+pub fn quad_in(t: f32) -> f32 {
+	t * t
+}
+
+pub fn quad_out(t: f32) -> f32 {
+	1.0 - quad_in(1.0 - t)
+}
+
+pub fn quad_in_out(t: f32) -> f32 {
+	in_out(t, quad_in)
+}
+
+pub fn cubic_in(t: f32) -> f32 {
+	t * t * t
+}
+
+pub fn cubic_out(t: f32) -> f32 {
+	1.0 - cubic_in(1.0 - t)
+}
+
+pub fn cubic_in_out(t: f32) -> f32 {
+	in_out(t, cubic_in)
+}
+
+pub fn quart_in(t: f32) -> f32 {
+	t * t * t * t
+}
+
+pub fn quart_out(t: f32) -> f32 {
+	1.0 - quart_in(1.0 - t)
+}
+
+pub fn quart_in_out(t: f32) -> f32 {
+	in_out(t, quart_in)
+}
+
+pub fn quint_in(t: f32) -> f32 {
+	t * t * t * t * t
+}
+
+pub fn quint_out(t: f32) -> f32 {
+	1.0 - quint_in(1.0 - t)
+}
+
+pub fn quint_in_out(t: f32) -> f32 {
+	in_out(t, quint_in)
+}
+
+pub fn sine_in(t: f32) -> f32 {
+	1.0 - (t * PI * 0.5).cos()
+}
+
+pub fn sine_out(t: f32) -> f32 {
+	(t * PI * 0.5).sin()
+}
+
+pub fn sine_in_out(t: f32) -> f32 {
+	0.5 - 0.5 * (t * PI).cos()
+}
+
+pub fn expo_in(t: f32) -> f32 {
+	if t <= 0.0 {
+		0.0
+	} else {
+		2.0.powf(10.0 * t - 10.0)
+	}
+}
+
+pub fn expo_out(t: f32) -> f32 {
+	1.0 - expo_in(1.0 - t)
+}
+
+pub fn expo_in_out(t: f32) -> f32 {
+	in_out(t, expo_in)
+}
+
+pub fn circ_in(t: f32) -> f32 {
+	1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+pub fn circ_out(t: f32) -> f32 {
+	1.0 - circ_in(1.0 - t)
+}
+
+pub fn circ_in_out(t: f32) -> f32 {
+	in_out(t, circ_in)
+}
+
+/// Pulls back slightly below 0 before accelerating.
+pub fn back_in(t: f32) -> f32 {
+	let c = 1.70158;
+	t * t * ((c + 1.0) * t - c)
+}
+
+pub fn back_out(t: f32) -> f32 {
+	1.0 - back_in(1.0 - t)
+}
+
+/// Penner's back in-out, its overshoot constant is scaled by 1.525 so both ends pull back
+/// by about 10% like `back_in` does, instead of the 5% a mirrored `back_in` would give.
+pub fn back_in_out(t: f32) -> f32 {
+	let c = 1.70158 * 1.525;
+	let t = t * 2.0;
+	if t < 1.0 {
+		t * t * ((c + 1.0) * t - c) * 0.5
+	} else {
+		let t = t - 2.0;
+		(t * t * ((c + 1.0) * t + c) + 2.0) * 0.5
+	}
+}
+
+pub fn elastic_in(t: f32) -> f32 {
+	if t <= 0.0 || t >= 1.0 {
+		return t.clamp(0.0, 1.0);
+	}
+	-(2.0.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * TAU / 3.0).sin()
+}
+
+pub fn elastic_out(t: f32) -> f32 {
+	1.0 - elastic_in(1.0 - t)
+}
+
+/// Penner's elastic in-out, with a longer period than `elastic_in` so each half keeps
+/// about as many oscillations and both ends overshoot by about 12%.
+pub fn elastic_in_out(t: f32) -> f32 {
+	if t <= 0.0 || t >= 1.0 {
+		return t.clamp(0.0, 1.0);
+	}
+	let wave = ((t * 20.0 - 11.125) * TAU / 4.5).sin();
+	if t < 0.5 {
+		-(2.0.powf(t * 20.0 - 10.0)) * wave * 0.5
+	} else {
+		2.0.powf(10.0 - t * 20.0) * wave * 0.5 + 1.0
+	}
+}
+
+pub fn bounce_out(t: f32) -> f32 {
+	let n = 7.5625;
+	let d = 2.75;
+	if t < 1.0 / d {
+		n * t * t
+	} else if t < 2.0 / d {
+		let t = t - 1.5 / d;
+		n * t * t + 0.75
+	} else if t < 2.5 / d {
+		let t = t - 2.25 / d;
+		n * t * t + 0.9375
+	} else {
+		let t = t - 2.625 / d;
+		n * t * t + 0.984375
+	}
+}
+
+pub fn bounce_in(t: f32) -> f32 {
+	1.0 - bounce_out(1.0 - t)
+}
+
+pub fn bounce_in_out(t: f32) -> f32 {
+	in_out(t, bounce_in)
+}
+
+// The first half eases in, the second half is the mirrored ease in.
+fn in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+	if t < 0.5 {
+		ease_in(t * 2.0) * 0.5
+	} else {
+		1.0 - ease_in(2.0 - t * 2.0) * 0.5
+	}
+}
+
+// Iñigo Quilez shaping functions
+
+/// Identity above `m`, below it smoothly approaches `n` at 0 instead of going to 0.
+pub fn almost_identity(x: f32, m: f32, n: f32) -> f32 {
+	if x > m {
+		return x;
+	}
+	let a = 2.0 * n - m;
+	let b = 2.0 * m - 3.0 * n;
+	let t = x / m;
+	(a * t + b) * t * t + n
+}
+
+/// Rises quickly to 1.0 at `x = 1 / k` and decays slowly afterwards.
+pub fn exp_impulse(x: f32, k: f32) -> f32 {
+	let h = k * x;
+	h * (1.0 - h).exp()
+}
+
+/// Smooth bump of half width `w` centered at `c`, a cheap gaussian replacement.
+pub fn cubic_pulse(c: f32, w: f32, x: f32) -> f32 {
+	let x = (x - c).abs();
+	if x > w {
+		return 0.0;
+	}
+	let x = x / w;
+	1.0 - x * x * (3.0 - 2.0 * x)
+}
+
+/// S-curve for `k > 1`, inverse S-curve for `k < 1`, identity at `k = 1`.
+pub fn gain(x: f32, k: f32) -> f32 {
+	let a = 0.5 * (2.0 * if x < 0.5 { x } else { 1.0 - x }).powf(k);
+	if x < 0.5 { a } else { 1.0 - a }
+}
+
+/// 0 at both ends and 1 in the middle, `k` controls the sharpness.
+pub fn parabola(x: f32, k: f32) -> f32 {
+	(4.0 * x * (1.0 - x)).powf(k)
+}
+
+/// Skewed parabola that peaks at `a / (a + b)` with a maximum of 1.
+pub fn pcurve(x: f32, a: f32, b: f32) -> f32 {
+	let k = (a + b).powf(a + b) / (a.powf(a) * b.powf(b));
+	k * x.powf(a) * (1.0 - x).powf(b)
+}
+
+// Interpolation, works for f32 and the glam vector types.
+
+/// Cubic Hermite spline from `p0` to `p1` with the tangents `m0` and `m1`.
+pub fn hermite<T>(p0: T, m0: T, p1: T, m1: T, t: f32) -> T
+where
+	T: Copy + Add<Output = T> + Mul<f32, Output = T>,
+{
+	let t2 = t * t;
+	let t3 = t2 * t;
+	p0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+		+ m0 * (t3 - 2.0 * t2 + t)
+		+ p1 * (-2.0 * t3 + 3.0 * t2)
+		+ m1 * (t3 - t2)
+}
+
+/// Uniform Catmull-Rom spline between `p1` and `p2`, passing through all control points.
+pub fn catmull_rom<T>(p0: T, p1: T, p2: T, p3: T, t: f32) -> T
+where
+	T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+	hermite(p1, (p2 - p0) * 0.5, p2, (p3 - p1) * 0.5, t)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::{Vec2, vec2};
+
+	type Ease = fn(f32) -> f32;
+
+	const EASINGS: [(&str, Ease); 30] = [
+		("quad_in", quad_in),
+		("quad_out", quad_out),
+		("quad_in_out", quad_in_out),
+		("cubic_in", cubic_in),
+		("cubic_out", cubic_out),
+		("cubic_in_out", cubic_in_out),
+		("quart_in", quart_in),
+		("quart_out", quart_out),
+		("quart_in_out", quart_in_out),
+		("quint_in", quint_in),
+		("quint_out", quint_out),
+		("quint_in_out", quint_in_out),
+		("sine_in", sine_in),
+		("sine_out", sine_out),
+		("sine_in_out", sine_in_out),
+		("expo_in", expo_in),
+		("expo_out", expo_out),
+		("expo_in_out", expo_in_out),
+		("circ_in", circ_in),
+		("circ_out", circ_out),
+		("circ_in_out", circ_in_out),
+		("back_in", back_in),
+		("back_out", back_out),
+		("back_in_out", back_in_out),
+		("elastic_in", elastic_in),
+		("elastic_out", elastic_out),
+		("elastic_in_out", elastic_in_out),
+		("bounce_in", bounce_in),
+		("bounce_out", bounce_out),
+		("bounce_in_out", bounce_in_out),
+	];
+
+	fn assert_close(a: f32, b: f32) {
+		assert!((a - b).abs() < 1e-4, "{a} != {b}");
+	}
+
+	#[test]
+	fn easings_hit_endpoints() {
+		for (name, ease) in EASINGS {
+			assert!(ease(0.0).abs() < 1e-3, "{name}(0) = {}", ease(0.0));
+			assert!((ease(1.0) - 1.0).abs() < 1e-3, "{name}(1) = {}", ease(1.0));
+			if name.ends_with("in_out") {
+				assert_close(ease(0.5), 0.5);
+			}
+		}
+		assert_close(quad_in(0.5), 0.25);
+		assert_close(cubic_out(0.5), 0.875);
+		assert!(back_in(0.2) < 0.0);
+		assert!(elastic_out(0.2) > 1.0);
+		// in-out overshoots on both sides, as far as the reference curves
+		assert_close(back_in_out(0.25), -0.09968);
+		assert_close(back_in_out(0.75), 1.09968);
+		assert_close(elastic_in_out(0.404), -0.11835);
+		assert_close(elastic_in_out(0.596), 1.11835);
+		// bounce segments join up
+		assert_close(bounce_out(1.0 / 2.75), 1.0);
+	}
+
+	#[test]
+	fn smootherstep_edges() {
+		assert_eq!(smootherstep(1.0, 2.0, 0.5), 0.0);
+		assert_eq!(smootherstep(1.0, 2.0, 3.0), 1.0);
+		assert_close(smootherstep(1.0, 2.0, 1.5), 0.5);
+		// flatter than smoothstep near the edges
+		let t = 0.1;
+		assert!(smootherstep(0.0, 1.0, t) < t * t * (3.0 - 2.0 * t));
+	}
+
+	#[test]
+	fn quilez_functions() {
+		assert_close(almost_identity(0.0, 0.2, 0.05), 0.05);
+		assert_close(almost_identity(0.2, 0.2, 0.05), 0.2);
+		assert_close(almost_identity(0.5, 0.2, 0.05), 0.5);
+		assert_close(exp_impulse(0.25, 4.0), 1.0);
+		assert_close(cubic_pulse(0.5, 0.2, 0.5), 1.0);
+		assert_close(cubic_pulse(0.5, 0.2, 0.7), 0.0);
+		assert_close(gain(0.3, 1.0), 0.3);
+		assert!(gain(0.3, 2.0) < 0.3 && gain(0.7, 2.0) > 0.7);
+		assert_close(gain(0.5, 3.0), 0.5);
+		assert_close(parabola(0.5, 2.0), 1.0);
+		assert_close(parabola(0.0, 2.0), 0.0);
+		assert_close(pcurve(0.25, 1.0, 3.0), 1.0);
+		assert!(pcurve(0.5, 1.0, 3.0) < 1.0);
+	}
+
+	#[test]
+	fn splines() {
+		assert_close(hermite(1.0, 0.0, 3.0, 0.0, 0.5), 2.0);
+		assert_close(hermite(1.0, 5.0, 3.0, -2.0, 1.0), 3.0);
+		// tangent at the start
+		let h = 1e-3;
+		let slope = (hermite(1.0, 5.0, 3.0, -2.0, h) - 1.0) / h;
+		assert!((slope - 5.0).abs() < 1e-2);
+
+		// evenly spaced points on a line are interpolated linearly
+		let p = |x: f32| vec2(x, 2.0 * x);
+		let v: Vec2 = catmull_rom(p(0.0), p(1.0), p(2.0), p(3.0), 0.25);
+		assert!((v - p(1.25)).length() < 1e-5);
+		assert_close(catmull_rom(0.0, 1.0, 4.0, 9.0, 0.0), 1.0);
+		assert_close(catmull_rom(0.0, 1.0, 4.0, 9.0, 1.0), 4.0);
+	}
+}
@@ -0,0 +1,384 @@
+// Maps a point to the tile that contains it for various tilings. The point is given in tile
+// units, scale the UV by the number of tiles first.
+
+use crate::voronoi::cell_hash;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{IVec2, Vec2, ivec2, vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{IVec2, Vec2, ivec2, vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+const SQRT_3: f32 = 1.732_050_8;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+	/// Integer tile id, its meaning depends on the tiling.
+	pub id: IVec2,
+	/// Center of the tile in the input space.
+	pub center: Vec2,
+	/// Position relative to the tile center.
+	pub local: Vec2,
+}
+
+fn floor_cell(p: Vec2) -> IVec2 {
+	ivec2(p.x.floor() as i32, p.y.floor() as i32)
+}
+
+/// Unit squares, the id is the cell index.
+pub fn square(p: Vec2) -> Tile {
+	let id = floor_cell(p);
+	let center = id.as_vec2() + 0.5;
+	Tile {
+		id,
+		center,
+		local: p - center,
+	}
+}
+
+/// The eight surrounding squares, starting with the edge neighbours +x, +y, -x, -y.
+pub const SQUARE_NEIGHBORS: [IVec2; 8] = [
+	IVec2::new(1, 0),
+	IVec2::new(0, 1),
+	IVec2::new(-1, 0),
+	IVec2::new(0, -1),
+	IVec2::new(1, 1),
+	IVec2::new(-1, 1),
+	IVec2::new(-1, -1),
+	IVec2::new(1, -1),
+];
+
+#[allow(clippy::needless_range_loop)]
+pub fn square_neighbors(id: IVec2) -> [IVec2; 8] {
+	let mut n = SQUARE_NEIGHBORS;
+	for i in 0..8 {
+		n[i] += id;
+	}
+	n
+}
+
+/// Offsets of the six hex neighbours in axial coordinates, the same for both orientations.
+pub const HEX_NEIGHBORS: [IVec2; 6] = [
+	IVec2::new(1, 0),
+	IVec2::new(1, -1),
+	IVec2::new(0, -1),
+	IVec2::new(-1, 0),
+	IVec2::new(-1, 1),
+	IVec2::new(0, 1),
+];
+
+#[allow(clippy::needless_range_loop)]
+pub fn hex_neighbors(id: IVec2) -> [IVec2; 6] {
+	let mut n = HEX_NEIGHBORS;
+	for i in 0..6 {
+		n[i] += id;
+	}
+	n
+}
+
+fn hex_round(q: f32, r: f32) -> IVec2 {
+	let s = -q - r;
+	let (mut rq, mut rr, rs) = (q.round(), r.round(), s.round());
+	let dq = (rq - q).abs();
+	let dr = (rr - r).abs();
+	let ds = (rs - s).abs();
+	if dq > dr && dq > ds {
+		rq = -rr - rs;
+	} else if dr > ds {
+		rr = -rq - rs;
+	}
+	ivec2(rq as i32, rr as i32)
+}
+
+/// Center of a pointy topped hex from its axial id.
+pub fn hex_pointy_center(id: IVec2) -> Vec2 {
+	let id = id.as_vec2();
+	vec2(id.x + id.y * 0.5, id.y * SQRT_3 * 0.5)
+}
+
+/// Pointy topped hexagons with neighbouring centers 1 apart. The id is axial, q along x
+/// and r along the rows.
+pub fn hex_pointy(p: Vec2) -> Tile {
+	let r = p.y * 2.0 / SQRT_3;
+	let id = hex_round(p.x - r * 0.5, r);
+	let center = hex_pointy_center(id);
+	Tile {
+		id,
+		center,
+		local: p - center,
+	}
+}
+
+pub fn hex_flat_center(id: IVec2) -> Vec2 {
+	let c = hex_pointy_center(id);
+	vec2(c.y, c.x)
+}
+
+/// Flat topped hexagons, the pointy tiling mirrored at the diagonal, so q runs along y.
+pub fn hex_flat(p: Vec2) -> Tile {
+	let t = hex_pointy(vec2(p.y, p.x));
+	Tile {
+		id: t.id,
+		center: vec2(t.center.y, t.center.x),
+		local: vec2(t.local.y, t.local.x),
+	}
+}
+
+/// Equilateral triangles with side length 1 and one side on the x axis. `id.y` is the row,
+/// `id.x` counts the triangles along the row with odd ids pointing down.
+pub fn triangle(p: Vec2) -> Tile {
+	let row = p.y * 2.0 / SQRT_3;
+	let skewed = vec2(p.x - row * 0.5, row);
+	let cell = floor_cell(skewed);
+	let f = skewed - cell.as_vec2();
+	let down = (f.x + f.y > 1.0) as i32;
+	let id = ivec2(cell.x * 2 + down, cell.y);
+	let center = triangle_center(id);
+	Tile {
+		id,
+		center,
+		local: p - center,
+	}
+}
+
+pub fn triangle_points_down(id: IVec2) -> bool {
+	id.x & 1 == 1
+}
+
+pub fn triangle_center(id: IVec2) -> Vec2 {
+	let q = id.x.div_euclid(2) as f32;
+	let r = id.y as f32;
+	let c = if triangle_points_down(id) {
+		2.0 / 3.0
+	} else {
+		1.0 / 3.0
+	};
+	let (q, r) = (q + c, r + c);
+	vec2(q + r * 0.5, r * SQRT_3 * 0.5)
+}
+
+/// The three triangles sharing an edge.
+pub fn triangle_neighbors(id: IVec2) -> [IVec2; 3] {
+	if triangle_points_down(id) {
+		[id - ivec2(1, 0), id + ivec2(1, 0), id + ivec2(-1, 1)]
+	} else {
+		[id + ivec2(1, 0), id - ivec2(1, 0), id + ivec2(1, -1)]
+	}
+}
+
+/// Unit bricks where every row is shifted by `offset` against the row below, 0.5 gives
+/// the running bond.
+pub fn brick(p: Vec2, offset: f32) -> Tile {
+	let row = p.y.floor();
+	let id = ivec2((p.x - row * offset).floor() as i32, row as i32);
+	let center = brick_center(id, offset);
+	Tile {
+		id,
+		center,
+		local: p - center,
+	}
+}
+
+pub fn brick_center(id: IVec2, offset: f32) -> Vec2 {
+	vec2(id.x as f32 + id.y as f32 * offset, id.y as f32) + 0.5
+}
+
+/// Left, right and the two bricks touching from above and below. When the rows line up
+/// the second brick of a row only touches at a corner.
+pub fn brick_neighbors(id: IVec2, offset: f32) -> [IVec2; 6] {
+	let shift = offset - offset.floor();
+	let above = id.x - offset.floor() as i32 - (shift > 0.0) as i32;
+	let below = id.x + offset.floor() as i32;
+	[
+		id - ivec2(1, 0),
+		id + ivec2(1, 0),
+		ivec2(above, id.y + 1),
+		ivec2(above + 1, id.y + 1),
+		ivec2(below, id.y - 1),
+		ivec2(below + 1, id.y - 1),
+	]
+}
+
+// Herringbone of 2x1 bricks on the unit grid. Horizontal bricks start on the cells with
+// x - y = 0 mod 4, vertical bricks on x - y = 3 mod 4 and extend upwards.
+fn herringbone_brick(cell: IVec2) -> IVec2 {
+	match (cell.x - cell.y).rem_euclid(4) {
+		1 => cell - ivec2(1, 0),
+		2 => cell - ivec2(0, 1),
+		_ => cell,
+	}
+}
+
+pub fn herringbone_is_horizontal(id: IVec2) -> bool {
+	(id.x - id.y).rem_euclid(4) == 0
+}
+
+pub fn herringbone_center(id: IVec2) -> Vec2 {
+	let size = if herringbone_is_horizontal(id) {
+		vec2(2.0, 1.0)
+	} else {
+		vec2(1.0, 2.0)
+	};
+	id.as_vec2() + size * 0.5
+}
+
+/// Herringbone of 2x1 bricks. The id is the lower left cell of the brick and `local` is
+/// rotated so that the long side of the brick always runs along x.
+pub fn herringbone(p: Vec2) -> Tile {
+	let id = herringbone_brick(floor_cell(p));
+	let center = herringbone_center(id);
+	let d = p - center;
+	let local = if herringbone_is_horizontal(id) {
+		d
+	} else {
+		vec2(d.y, -d.x)
+	};
+	Tile { id, center, local }
+}
+
+/// The six bricks sharing an edge with the brick.
+pub fn herringbone_neighbors(id: IVec2) -> [IVec2; 6] {
+	let cells = if herringbone_is_horizontal(id) {
+		[
+			ivec2(-1, 0),
+			ivec2(2, 0),
+			ivec2(0, -1),
+			ivec2(1, -1),
+			ivec2(0, 1),
+			ivec2(1, 1),
+		]
+	} else {
+		[
+			ivec2(-1, 0),
+			ivec2(1, 0),
+			ivec2(-1, 1),
+			ivec2(1, 1),
+			ivec2(0, -1),
+			ivec2(0, 2),
+		]
+	};
+	let mut n = [IVec2::ZERO; 6];
+	for i in 0..6 {
+		n[i] = herringbone_brick(id + cells[i]);
+	}
+	n
+}
+
+/// Quarter turns of the Truchet tile, from 0 to 3.
+pub fn truchet_rotation(id: IVec2, seed: u32) -> u32 {
+	let h = cell_hash(id + ivec2(seed as i32, 0));
+	(h.x * 4.0) as u32 & 3
+}
+
+/// Unit squares with `local` randomly rotated by quarter turns, so drawing one motif in
+/// local space gives a Truchet pattern.
+pub fn truchet(p: Vec2, seed: u32) -> Tile {
+	let t = square(p);
+	let l = t.local;
+	let local = match truchet_rotation(t.id, seed) {
+		0 => l,
+		1 => vec2(-l.y, l.x),
+		2 => -l,
+		_ => vec2(l.y, -l.x),
+	};
+	Tile { local, ..t }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn points() -> impl Iterator<Item = Vec2> {
+		(0..400).map(|i| (cell_hash(ivec2(i, 5)) - 0.5) * 12.0)
+	}
+
+	#[test]
+	fn squares_and_truchet() {
+		let t = square(vec2(-0.25, 2.75));
+		assert_eq!(t.id, ivec2(-1, 2));
+		assert_eq!(t.local, vec2(0.25, 0.25));
+		assert_eq!(square_neighbors(ivec2(3, 4))[1], ivec2(3, 5));
+
+		let mut rotations = [0; 4];
+		for p in points() {
+			let t = truchet(p, 7);
+			assert_eq!(t.id, square(p).id);
+			assert!((t.local.length() - square(p).local.length()).abs() < 1e-6);
+			rotations[truchet_rotation(t.id, 7) as usize] += 1;
+		}
+		assert!(rotations.iter().all(|r| *r > 50));
+	}
+
+	#[test]
+	fn hexagons_pick_the_nearest_center() {
+		for p in points() {
+			for (tile, center) in [
+				(hex_pointy(p), hex_pointy_center as fn(IVec2) -> Vec2),
+				(hex_flat(p), hex_flat_center),
+			] {
+				assert!((tile.center + tile.local - p).length() < 1e-4);
+				assert!(tile.local.length() <= 1.0 / SQRT_3 + 1e-4);
+				for n in hex_neighbors(tile.id) {
+					assert!((center(n) - tile.center).length() - 1.0 < 1e-4);
+					assert!((p - center(n)).length() >= tile.local.length() - 1e-4);
+				}
+			}
+		}
+		// pointy hexes have neighbours left and right, flat ones above and below
+		assert_eq!(hex_pointy_center(ivec2(1, 0)), vec2(1.0, 0.0));
+		assert_eq!(hex_flat_center(ivec2(1, 0)), vec2(0.0, 1.0));
+	}
+
+	#[test]
+	fn triangles_and_bricks() {
+		for p in points() {
+			let t = triangle(p);
+			// the inradius of a unit triangle
+			let inradius = 0.5 / SQRT_3;
+			assert!(t.local.length() <= 2.0 * inradius + 1e-4);
+			for n in triangle_neighbors(t.id) {
+				assert!(triangle_points_down(n) != triangle_points_down(t.id));
+				assert!(((triangle_center(n) - t.center).length() - 2.0 * inradius).abs() < 1e-4);
+			}
+
+			for offset in [0.0, 0.5, 1.0 / 3.0, 1.25] {
+				let b = brick(p, offset);
+				assert!(b.local.abs().max_element() <= 0.5 + 1e-5);
+				for n in brick_neighbors(b.id, offset) {
+					let d = brick_center(n, offset) - b.center;
+					assert!(d.x.abs() <= 1.0 + 1e-5 && d.y.abs() <= 1.0, "{offset} {n}");
+					assert_ne!(n, b.id);
+				}
+			}
+		}
+		assert_eq!(brick(vec2(0.25, 1.5), 0.5).id, ivec2(-1, 1));
+	}
+
+	#[test]
+	fn herringbone_bricks() {
+		for p in points() {
+			let t = herringbone(p);
+			assert!(t.local.x.abs() <= 1.0 && t.local.y.abs() <= 0.5, "{p}");
+			let neighbors = herringbone_neighbors(t.id);
+			for (i, n) in neighbors.iter().enumerate() {
+				assert_ne!(*n, t.id);
+				assert!(!neighbors[..i].contains(n));
+				assert!(herringbone_is_horizontal(*n) || (n.x - n.y).rem_euclid(4) == 3);
+			}
+		}
+		// both cells of a brick map to the same id
+		assert_eq!(
+			herringbone(vec2(0.5, 0.5)).id,
+			herringbone(vec2(1.5, 0.5)).id
+		);
+		assert_eq!(
+			herringbone(vec2(0.5, 1.5)).id,
+			herringbone(vec2(0.5, 2.5)).id
+		);
+		assert!(!herringbone_is_horizontal(herringbone(vec2(0.5, 1.5)).id));
+	}
+}
@@ -0,0 +1,230 @@
+// Tone mapping operators from linear HDR to linear display values in [0, 1]. Encode the
+// result with `color::linear_to_srgb_rgb` or write it to an sRGB target.
+
+#[cfg(not(target_arch = "spirv"))]
+use glam::{Mat3, Vec3, vec3};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{Mat3, Vec3, vec3};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+use trivalibs_nostd::prelude::*;
+
+/// Scales the color by `2^ev` exposure stops.
+pub fn exposure(c: Vec3, ev: f32) -> Vec3 {
+	c * 2.0.powf(ev)
+}
+
+/// Exposure factor of a physical camera with the given EV at ISO 100.
+pub fn exposure_from_ev100(ev100: f32) -> f32 {
+	1.0 / (1.2 * 2.0.powf(ev100))
+}
+
+/// Exposure factor that maps the average scene luminance to `key`, 0.18 is middle gray.
+pub fn exposure_from_luminance(average_luminance: f32, key: f32) -> f32 {
+	key / average_luminance.max(1e-6)
+}
+
+pub fn reinhard(c: Vec3) -> Vec3 {
+	c / (1.0 + c)
+}
+
+/// Reinhard with a `white` point that maps to exactly 1.0 instead of infinity.
+pub fn reinhard_extended(c: Vec3, white: f32) -> Vec3 {
+	c * (1.0 + c / (white * white)) / (1.0 + c)
+}
+
+fn aces_rrt_odt_fit(v: Vec3) -> Vec3 {
+	let a = v * (v + 0.024_578_6) - 0.000_090_537;
+	let b = v * (0.983_729 * v + 0.432_951) + 0.238_081;
+	a / b
+}
+
+/// Stephen Hill's fit of the ACES reference rendering and output transforms.
+pub fn aces_fitted(c: Vec3) -> Vec3 {
+	// sRGB to ACEScg with the RRT saturation applied
+	let input = Mat3::from_cols(
+		vec3(0.59719, 0.07600, 0.02840),
+		vec3(0.35458, 0.90834, 0.13383),
+		vec3(0.04823, 0.01566, 0.83777),
+	);
+	// ODT saturation and back to sRGB
+	let output = Mat3::from_cols(
+		vec3(1.60475, -0.10208, -0.00327),
+		vec3(-0.53108, 1.10813, -0.07276),
+		vec3(-0.07367, -0.00605, 1.07602),
+	);
+	(output * aces_rrt_odt_fit(input * c)).clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+fn agx_contrast(x: Vec3) -> Vec3 {
+	let x2 = x * x;
+	let x4 = x2 * x2;
+	15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232
+}
+
+/// AgX base look, after Benjamin Wrensch's polynomial approximation of the sigmoid.
+#[allow(clippy::excessive_precision)]
+pub fn agx(c: Vec3) -> Vec3 {
+	let inset = Mat3::from_cols(
+		vec3(0.842479062253094, 0.0423282422610123, 0.0423756549057051),
+		vec3(0.0784335999999992, 0.878468636469772, 0.0784336),
+		vec3(0.0792237451477643, 0.0791661274605434, 0.879142973793104),
+	);
+	let outset = Mat3::from_cols(
+		vec3(1.19687900512017, -0.0528968517574562, -0.0529716355144438),
+		vec3(-0.0980208811401368, 1.15190312990417, -0.0980434501171241),
+		vec3(-0.0990297440797205, -0.0989611768448433, 1.15107367264116),
+	);
+	let min_ev = -12.47393;
+	let max_ev = 4.026069;
+
+	let c = (inset * c).max(Vec3::splat(1e-10));
+	let c = vec3(c.x.log2(), c.y.log2(), c.z.log2()).clamp(Vec3::splat(min_ev), Vec3::splat(max_ev));
+	let c = agx_contrast((c - min_ev) / (max_ev - min_ev));
+	// the curve outputs display encoded values with a 2.2 gamma
+	let c = (outset * c).clamp(Vec3::ZERO, Vec3::ONE);
+	vec3(c.x.powf(2.2), c.y.powf(2.2), c.z.powf(2.2))
+}
+
+fn uchimura_channel(x: f32) -> f32 {
+	// max brightness, contrast, linear start and length, black tightness, pedestal
+	let (p, a, m, l, c, b) = (1.0, 1.0, 0.22, 0.4, 1.33, 0.0);
+
+	let l0 = (p - m) * l / a;
+	let s0 = m + l0;
+	let s1 = m + a * l0;
+	let c2 = a * p / (p - s1);
+	let cp = -c2 / p;
+
+	let w0 = 1.0 - x.smoothstep(0.0, m);
+	let w2 = x.step(m + l0);
+	let w1 = 1.0 - w0 - w2;
+
+	let toe = m * (x / m).powf(c) + b;
+	let shoulder = p - (p - s1) * (cp * (x - s0)).exp();
+	let linear = m + a * (x - m);
+	toe * w0 + linear * w1 + shoulder * w2
+}
+
+/// Hajime Uchimura's Gran Turismo operator with its default parameters, a linear middle
+/// section between a toe and an exponential shoulder.
+pub fn uchimura(c: Vec3) -> Vec3 {
+	let c = c.max(Vec3::ZERO);
+	vec3(
+		uchimura_channel(c.x),
+		uchimura_channel(c.y),
+		uchimura_channel(c.z),
+	)
+}
+
+/// Khronos PBR Neutral, keeps base colors up to 0.8 close to their input.
+pub fn pbr_neutral(c: Vec3) -> Vec3 {
+	let start_compression = 0.8 - 0.04;
+	let desaturation = 0.15;
+
+	let x = c.min_element();
+	let offset = if x < 0.08 { x - 6.25 * x * x } else { 0.04 };
+	let c = c - offset;
+
+	let peak = c.max_element();
+	if peak < start_compression {
+		return c;
+	}
+
+	let d = 1.0 - start_compression;
+	let new_peak = 1.0 - d * d / (peak + d - start_compression);
+	let c = c * (new_peak / peak);
+
+	let g = 1.0 - 1.0 / (desaturation * (peak - new_peak) + 1.0);
+	c.lerp(Vec3::splat(new_peak), g)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	type ToneMap = fn(Vec3) -> Vec3;
+
+	// the ramp goes up to 40, extended Reinhard only stays in range below its white point
+	const OPERATORS: [(&str, ToneMap); 7] = [
+		("reinhard", reinhard),
+		("reinhard_extended", |c| reinhard_extended(c, 64.0)),
+		("aces_fitted", aces_fitted),
+		("agx", agx),
+		("uchimura", uchimura),
+		("pbr_neutral", pbr_neutral),
+		("exposure", |c| reinhard(exposure(c, 1.0))),
+	];
+
+	fn assert_close(a: f32, b: f32, eps: f32) {
+		assert!((a - b).abs() < eps, "{a} != {b}");
+	}
+
+	#[test]
+	fn monotonic_on_gray_ramp() {
+		for (name, op) in OPERATORS {
+			let mut last = -1.0;
+			for i in 0..2000 {
+				let x = (i as f32 * 0.01).powi(2) * 0.1;
+				let y = op(Vec3::splat(x));
+				assert!(y.x >= last - 1e-6, "{name} decreases at {x}");
+				assert!(
+					y.min_element() >= -1e-6 && y.max_element() <= 1.0 + 1e-6,
+					"{name}"
+				);
+				last = y.x;
+			}
+		}
+	}
+
+	#[test]
+	fn gray_stays_gray() {
+		for (name, op) in OPERATORS {
+			for x in [0.05, 0.18, 1.0, 8.0] {
+				let y = op(Vec3::splat(x));
+				assert!(y.max_element() - y.min_element() < 2e-3, "{name} {y}");
+			}
+		}
+	}
+
+	#[test]
+	fn reference_points() {
+		assert_close(reinhard(Vec3::ONE).x, 0.5, 1e-6);
+		assert_close(reinhard_extended(Vec3::splat(4.0), 4.0).x, 1.0, 1e-6);
+		assert_close(exposure(Vec3::ONE, 2.0).x, 4.0, 1e-6);
+		assert_close(exposure_from_luminance(0.36, 0.18), 0.5, 1e-6);
+
+		assert_close(aces_fitted(Vec3::ZERO).x, 0.0, 1e-6);
+		assert!(aces_fitted(Vec3::splat(100.0)).x > 0.99);
+
+		// below the compression start only the black offset is applied
+		assert_close(pbr_neutral(Vec3::splat(0.5)).x, 0.46, 1e-6);
+		assert!(pbr_neutral(Vec3::splat(100.0)).x > 0.99);
+
+		// identity in the linear section
+		assert_close(uchimura(Vec3::splat(0.3)).x, 0.3, 1e-6);
+		assert!(uchimura(Vec3::splat(100.0)).x > 0.99);
+
+		// middle gray lands around 0.5 display value
+		let mid = linear_to_display(agx(Vec3::splat(0.18)).x);
+		assert!(mid > 0.4 && mid < 0.6, "{mid}");
+	}
+
+	fn linear_to_display(x: f32) -> f32 {
+		x.powf(1.0 / 2.2)
+	}
+
+	#[test]
+	fn saturated_colors_desaturate_towards_white() {
+		let c = vec3(50.0, 5.0, 0.5);
+		for op in [aces_fitted, agx, pbr_neutral] {
+			let y = op(c);
+			assert!(y.x > y.y && y.y > y.z, "{y}");
+			assert!(y.z > 0.0);
+		}
+	}
+}
@@ -0,0 +1,198 @@
+// Maps the canvas UV to the UV of content with a different aspect ratio, and other UV space
+// transformations. The fitting modes return the content UV together with a mask that is 1
+// inside of the content and 0 outside.
+
+use core::f32::consts::TAU;
+#[cfg(not(target_arch = "spirv"))]
+use glam::{UVec2, Vec2, vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{UVec2, Vec2, vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FitUv {
+	pub uv: Vec2,
+	pub mask: f32,
+}
+
+fn in_bounds(uv: Vec2) -> f32 {
+	(uv.cmpge(Vec2::ZERO).all() && uv.cmple(Vec2::ONE).all()) as u32 as f32
+}
+
+// `content` is placed on the canvas with the given size in pixels and offset from the
+// upper left corner.
+fn place(uv: Vec2, size: UVec2, placed_size: Vec2, offset: Vec2) -> FitUv {
+	let uv = (uv * size.as_vec2() - offset) / placed_size;
+	FitUv {
+		uv,
+		mask: in_bounds(uv),
+	}
+}
+
+fn contain_scale(size: UVec2, content: Vec2) -> f32 {
+	let s = size.as_vec2() / content;
+	s.x.min(s.y)
+}
+
+/// Stretches the content over the whole canvas, ignoring its aspect ratio.
+pub fn stretch(uv: Vec2) -> FitUv {
+	FitUv { uv, mask: 1.0 }
+}
+
+/// Scales the content to fit completely into the canvas, aligned to the upper left
+/// corner. Only the aspect ratio of `content` matters.
+pub fn contain(uv: Vec2, size: UVec2, content: Vec2) -> FitUv {
+	let placed = content * contain_scale(size, content);
+	place(uv, size, placed, Vec2::ZERO)
+}
+
+/// Like `contain`, but centered with letterbox or pillarbox bars on both sides.
+pub fn contain_centered(uv: Vec2, size: UVec2, content: Vec2) -> FitUv {
+	let placed = content * contain_scale(size, content);
+	place(uv, size, placed, (size.as_vec2() - placed) * 0.5)
+}
+
+/// Scales the centered content to fill the whole canvas and crops the overflowing sides.
+pub fn cover(uv: Vec2, size: UVec2, content: Vec2) -> FitUv {
+	let s = size.as_vec2() / content;
+	let placed = content * s.x.max(s.y);
+	place(uv, size, placed, (size.as_vec2() - placed) * 0.5)
+}
+
+/// Centers content of `content` pixels unscaled, so that one content pixel covers one
+/// canvas pixel. The offset is rounded to whole pixels to keep texel centers aligned.
+pub fn pixel_exact(uv: Vec2, size: UVec2, content: UVec2) -> FitUv {
+	let offset = ((size.as_vec2() - content.as_vec2()) * 0.5).floor();
+	place(uv, size, content.as_vec2(), offset)
+}
+
+/// Angle in turns from 0 to 1, counter clockwise from +x, and the radius around `center`.
+pub fn to_polar(p: Vec2, center: Vec2) -> Vec2 {
+	let d = p - center;
+	let a = d.y.atan2(d.x) / TAU;
+	vec2(a - a.floor(), d.length())
+}
+
+pub fn from_polar(polar: Vec2, center: Vec2) -> Vec2 {
+	let (s, c) = (polar.x * TAU).sin_cos();
+	center + vec2(c, s) * polar.y
+}
+
+/// Like `to_polar` with the natural logarithm of the radius, which turns scaling around
+/// the center into a translation along y.
+pub fn to_log_polar(p: Vec2, center: Vec2) -> Vec2 {
+	let polar = to_polar(p, center);
+	vec2(polar.x, polar.y.max(1e-20).ln())
+}
+
+pub fn from_log_polar(log_polar: Vec2, center: Vec2) -> Vec2 {
+	from_polar(vec2(log_polar.x, log_polar.y.exp()), center)
+}
+
+/// Rotates `p` counter clockwise by `angle` radians around `pivot`.
+pub fn rotate_around(p: Vec2, pivot: Vec2, angle: f32) -> Vec2 {
+	let (s, c) = angle.sin_cos();
+	let d = p - pivot;
+	pivot + vec2(c * d.x - s * d.y, s * d.x + c * d.y)
+}
+
+/// Scales `p` away from `pivot`. To zoom into content, scale its UV by the inverse.
+pub fn scale_around(p: Vec2, pivot: Vec2, scale: Vec2) -> Vec2 {
+	pivot + (p - pivot) * scale
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use glam::uvec2;
+
+	fn assert_close(a: Vec2, b: Vec2) {
+		assert!((a - b).abs().max_element() < 1e-5, "{a} != {b}");
+	}
+
+	#[test]
+	fn contain_and_cover() {
+		// a square image on a 200x100 canvas
+		let size = uvec2(200, 100);
+		let square = Vec2::ONE;
+
+		let fit = contain(vec2(0.25, 0.5), size, square);
+		assert_close(fit.uv, vec2(0.5, 0.5));
+		assert_eq!(fit.mask, 1.0);
+		assert_eq!(contain(vec2(0.75, 0.5), size, square).mask, 0.0);
+
+		let fit = contain_centered(vec2(0.5, 0.5), size, square);
+		assert_close(fit.uv, vec2(0.5, 0.5));
+		assert_close(
+			contain_centered(vec2(0.25, 0.0), size, square).uv,
+			vec2(0.0, 0.0),
+		);
+		assert_eq!(contain_centered(vec2(0.2, 0.5), size, square).mask, 0.0);
+		assert_eq!(contain_centered(vec2(0.8, 0.5), size, square).mask, 0.0);
+
+		// cover crops top and bottom
+		let fit = cover(vec2(0.0, 0.0), size, square);
+		assert_close(fit.uv, vec2(0.0, 0.25));
+		assert_close(cover(vec2(1.0, 1.0), size, square).uv, vec2(1.0, 0.75));
+		for i in 0..=10 {
+			let uv = Vec2::splat(i as f32 / 10.0);
+			assert_eq!(cover(uv, size, square).mask, 1.0);
+			assert_eq!(stretch(uv).uv, uv);
+		}
+	}
+
+	#[test]
+	fn pixel_exact_mapping() {
+		let size = uvec2(100, 50);
+		let content = uvec2(10, 10);
+		// canvas pixel 45 is the first column of the content
+		let fit = pixel_exact(vec2(45.5 / 100.0, 20.5 / 50.0), size, content);
+		assert_close(fit.uv, vec2(0.05, 0.05));
+		assert_eq!(fit.mask, 1.0);
+		assert_eq!(
+			pixel_exact(vec2(44.5 / 100.0, 0.5), size, content).mask,
+			0.0
+		);
+		// odd differences round down
+		let fit = pixel_exact(vec2(45.5 / 101.0, 20.5 / 50.0), uvec2(101, 50), content);
+		assert_close(fit.uv, vec2(0.05, 0.05));
+	}
+
+	#[test]
+	fn polar_round_trips() {
+		let center = vec2(0.5, 0.5);
+		assert_close(to_polar(vec2(0.5, 1.0), center), vec2(0.25, 0.5));
+		assert_close(to_polar(vec2(0.5, 0.0), center), vec2(0.75, 0.5));
+		for i in 0..20 {
+			let p = vec2(i as f32 * 0.13 - 1.0, 0.7 - i as f32 * 0.09);
+			assert_close(from_polar(to_polar(p, center), center), p);
+			assert_close(from_log_polar(to_log_polar(p, center), center), p);
+			let a = to_polar(p, center).x;
+			assert!((0.0..1.0).contains(&a));
+		}
+		// doubling the distance moves by ln(2) in log polar space
+		let a = to_log_polar(vec2(0.7, 0.5), center);
+		let b = to_log_polar(vec2(0.9, 0.5), center);
+		assert!((b.y - a.y - 2.0f32.ln()).abs() < 1e-5);
+	}
+
+	#[test]
+	fn pivot_transforms() {
+		let pivot = vec2(0.5, 0.5);
+		assert_close(
+			rotate_around(vec2(1.0, 0.5), pivot, core::f32::consts::FRAC_PI_2),
+			vec2(0.5, 1.0),
+		);
+		assert_close(rotate_around(pivot, pivot, 1.3), pivot);
+		assert_close(
+			scale_around(vec2(1.0, 0.0), pivot, vec2(2.0, 0.5)),
+			vec2(1.5, 0.25),
+		);
+		assert_close(scale_around(pivot, pivot, Vec2::splat(3.0)), pivot);
+	}
+}
@@ -0,0 +1,230 @@
+// Cellular noise on a unit grid with one jittered feature point per cell.
+
+#[cfg(not(target_arch = "spirv"))]
+use glam::{IVec2, Vec2, ivec2, vec2};
+#[cfg(not(target_arch = "spirv"))]
+#[allow(unused_imports)]
+use num_traits::Float;
+#[cfg(target_arch = "spirv")]
+use spirv_std::glam::{IVec2, Vec2, ivec2, vec2};
+#[cfg(target_arch = "spirv")]
+#[allow(unused_imports)]
+use spirv_std::num_traits::Float;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Metric {
+	Euclidean,
+	Manhattan,
+	Chebyshev,
+}
+
+impl Metric {
+	pub fn distance(self, d: Vec2) -> f32 {
+		match self {
+			Metric::Euclidean => d.length(),
+			Metric::Manhattan => d.x.abs() + d.y.abs(),
+			Metric::Chebyshev => d.x.abs().max(d.y.abs()),
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Voronoi {
+	/// Distance to the closest feature point.
+	pub f1: f32,
+	/// Distance to the second closest feature point.
+	pub f2: f32,
+	/// Grid cell of the closest feature point, wrapped into the period when tiled.
+	pub cell: IVec2,
+	/// Position of the closest feature point, in the same space as the input point.
+	pub center: Vec2,
+	/// Distance to the closest cell border. Exact for `Metric::Euclidean`, for the other
+	/// metrics it is approximated by `(f2 - f1) / 2`.
+	pub edge: f32,
+}
+
+// pcg2d from "Hash Functions for GPU Rendering", Jarzynski and Olano 2020.
+fn pcg2d(x: u32, y: u32) -> (u32, u32) {
+	let mut x = x.wrapping_mul(1664525).wrapping_add(1013904223);
+	let mut y = y.wrapping_mul(1664525).wrapping_add(1013904223);
+	x = x.wrapping_add(y.wrapping_mul(1664525));
+	y = y.wrapping_add(x.wrapping_mul(1664525));
+	x ^= x >> 16;
+	y ^= y >> 16;
+	x = x.wrapping_add(y.wrapping_mul(1664525));
+	y = y.wrapping_add(x.wrapping_mul(1664525));
+	x ^= x >> 16;
+	y ^= y >> 16;
+	(x, y)
+}
+
+/// Random point in [0, 1)² for a cell, stable across CPU and GPU.
+pub fn cell_hash(cell: IVec2) -> Vec2 {
+	let (x, y) = pcg2d(cell.x as u32, cell.y as u32);
+	vec2((x >> 8) as f32, (y >> 8) as f32) / 16777216.0
+}
+
+/// Feature point of a cell relative to its lower corner. A `jitter` of 0 puts it into the
+/// cell center, 1 anywhere in the cell.
+pub fn cell_point(cell: IVec2, jitter: f32) -> Vec2 {
+	vec2(0.5, 0.5) + (cell_hash(cell) - 0.5) * jitter.clamp(0.0, 1.0)
+}
+
+fn wrap_cell(cell: IVec2, period: IVec2) -> IVec2 {
+	if period.x <= 0 || period.y <= 0 {
+		return cell;
+	}
+	ivec2(cell.x.rem_euclid(period.x), cell.y.rem_euclid(period.y))
+}
+
+fn floor_cell(p: Vec2) -> IVec2 {
+	ivec2(p.x.floor() as i32, p.y.floor() as i32)
+}
+
+/// Cellular noise with one feature point per unit cell.
+pub fn voronoi(p: Vec2, jitter: f32, metric: Metric) -> Voronoi {
+	voronoi_tiled(p, IVec2::ZERO, jitter, metric)
+}
+
+/// Cellular noise that repeats every `period` cells. A zero or negative period disables
+/// the tiling.
+pub fn voronoi_tiled(p: Vec2, period: IVec2, jitter: f32, metric: Metric) -> Voronoi {
+	let base = floor_cell(p);
+	let local = p - base.as_vec2();
+
+	// a 5x5 neighbourhood keeps f2 exact for any jitter up to 1
+	let mut f1 = f32::MAX;
+	let mut f2 = f32::MAX;
+	let mut closest = IVec2::ZERO;
+	let mut closest_r = Vec2::ZERO;
+	for y in -2..=2 {
+		for x in -2..=2 {
+			let offset = ivec2(x, y);
+			let r = offset.as_vec2() + cell_point(wrap_cell(base + offset, period), jitter) - local;
+			let d = metric.distance(r);
+			if d < f1 {
+				f2 = f1;
+				f1 = d;
+				closest = offset;
+				closest_r = r;
+			} else if d < f2 {
+				f2 = d;
+			}
+		}
+	}
+
+	let edge = if metric == Metric::Euclidean {
+		// distance to the bisectors between the closest point and its neighbours
+		let mut edge = f32::MAX;
+		for y in -2..=2 {
+			for x in -2..=2 {
+				let offset = closest + ivec2(x, y);
+				let r = offset.as_vec2() + cell_point(wrap_cell(base + offset, period), jitter) - local;
+				let d = r - closest_r;
+				if d.length_squared() > 1e-8 {
+					edge = edge.min((closest_r + r).dot(d.normalize()) * 0.5);
+				}
+			}
+		}
+		edge
+	} else {
+		(f2 - f1) * 0.5
+	};
+
+	Voronoi {
+		f1,
+		f2,
+		cell: wrap_cell(base + closest, period),
+		center: p + closest_r,
+		edge,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const METRICS: [Metric; 3] = [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev];
+
+	fn brute_force(p: Vec2, jitter: f32, metric: Metric) -> (f32, f32, IVec2) {
+		let mut d = [f32::MAX; 2];
+		let mut cell = IVec2::ZERO;
+		for y in -8..8 {
+			for x in -8..8 {
+				let c = ivec2(x, y);
+				let dist = metric.distance(c.as_vec2() + cell_point(c, jitter) - p);
+				if dist < d[0] {
+					d = [dist, d[0]];
+					cell = c;
+				} else if dist < d[1] {
+					d[1] = dist;
+				}
+			}
+		}
+		(d[0], d[1], cell)
+	}
+
+	#[test]
+	fn matches_brute_force() {
+		for metric in METRICS {
+			for i in 0..200 {
+				let p = (cell_hash(ivec2(i, 7)) - 0.5) * 8.0;
+				let v = voronoi(p, 1.0, metric);
+				let (f1, f2, cell) = brute_force(p, 1.0, metric);
+				assert!((v.f1 - f1).abs() < 1e-5 && (v.f2 - f2).abs() < 1e-5, "{p}");
+				assert_eq!(v.cell, cell);
+				let center = cell.as_vec2() + cell_point(cell, 1.0);
+				assert!((v.center - center).length() < 1e-5);
+				assert!(v.f1 <= v.f2 && v.edge >= -1e-5);
+			}
+		}
+	}
+
+	#[test]
+	fn exact_edge_distance() {
+		// without jitter the borders are the grid lines between cell centers
+		let v = voronoi(vec2(0.3, 0.8), 0.0, Metric::Euclidean);
+		assert!((v.edge - 0.2).abs() < 1e-5);
+		assert_eq!(v.cell, ivec2(0, 0));
+		assert_eq!(v.center, vec2(0.5, 0.5));
+
+		// moving by the edge distance lands on the border where f1 equals f2
+		for i in 0..50 {
+			let p = cell_hash(ivec2(i, 3)) * 6.0;
+			let v = voronoi(p, 0.8, Metric::Euclidean);
+			let mut closest = f32::MAX;
+			for k in 0..64 {
+				let a = k as f32 / 64.0 * core::f32::consts::TAU;
+				let q = voronoi(
+					p + vec2(a.cos(), a.sin()) * v.edge * 1.01,
+					0.8,
+					Metric::Euclidean,
+				);
+				closest = closest.min(q.f2 - q.f1);
+			}
+			assert!(closest < 0.05, "{p} {closest}");
+		}
+	}
+
+	#[test]
+	fn metrics() {
+		let d = vec2(3.0, -4.0);
+		assert_eq!(Metric::Euclidean.distance(d), 5.0);
+		assert_eq!(Metric::Manhattan.distance(d), 7.0);
+		assert_eq!(Metric::Chebyshev.distance(d), 4.0);
+	}
+
+	#[test]
+	fn tiles_with_period() {
+		let period = ivec2(4, 3);
+		for i in 0..50 {
+			let p = cell_hash(ivec2(i, 11)) * vec2(4.0, 3.0);
+			let a = voronoi_tiled(p, period, 1.0, Metric::Euclidean);
+			let b = voronoi_tiled(p + vec2(4.0, -3.0), period, 1.0, Metric::Euclidean);
+			assert!((a.f1 - b.f1).abs() < 1e-4 && (a.edge - b.edge).abs() < 1e-4);
+			assert_eq!(a.cell, b.cell);
+			assert!(a.cell.x >= 0 && a.cell.x < 4 && a.cell.y >= 0 && a.cell.y < 3);
+			assert!((b.center - a.center - vec2(4.0, -3.0)).length() < 1e-4);
+		}
+	}
+}
@@ -0,0 +1,102 @@
+// Rasterizes the shapes into small grayscale images and compares them to the references in
+// `tests/golden`. Run with `UPDATE_GOLDEN=1` to rewrite the references after an intended
+// change.
+
+use glam::{Vec2, vec2};
+use shared_nostd::shapes::*;
+use std::{fs, path::PathBuf};
+
+const SIZE: u32 = 32;
+/// Allowed difference per pixel, in 8 bit steps.
+const TOLERANCE: u8 = 2;
+
+fn rasterize(shape: impl Fn(Vec2) -> f32) -> Vec<u8> {
+	let mut pixels = Vec::with_capacity((SIZE * SIZE) as usize);
+	for y in 0..SIZE {
+		for x in 0..SIZE {
+			let st = (vec2(x as f32, y as f32) + 0.5) / SIZE as f32;
+			pixels.push((shape(st).clamp(0.0, 1.0) * 255.0).round() as u8);
+		}
+	}
+	pixels
+}
+
+fn golden_path(name: &str) -> PathBuf {
+	PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+		.join("tests/golden")
+		.join(format!("{name}.png"))
+}
+
+fn write_png(path: &PathBuf, pixels: &[u8]) {
+	fs::create_dir_all(path.parent().unwrap()).unwrap();
+	let file = fs::File::create(path).unwrap();
+	let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), SIZE, SIZE);
+	encoder.set_color(png::ColorType::Grayscale);
+	encoder.set_depth(png::BitDepth::Eight);
+	let mut writer = encoder.write_header().unwrap();
+	writer.write_image_data(pixels).unwrap();
+}
+
+fn read_png(path: &PathBuf) -> Vec<u8> {
+	let bytes = fs::read(path).unwrap_or_else(|_| {
+		panic!(
+			"missing reference {}, run with UPDATE_GOLDEN=1 to create it",
+			path.display()
+		)
+	});
+	let mut reader = png::Decoder::new(std::io::Cursor::new(bytes))
+		.read_info()
+		.unwrap();
+	let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+	let info = reader.next_frame(&mut buf).unwrap();
+	assert_eq!((info.width, info.height), (SIZE, SIZE));
+	assert_eq!(info.color_type, png::ColorType::Grayscale);
+	buf.truncate(info.buffer_size());
+	buf
+}
+
+fn check(name: &str, shape: impl Fn(Vec2) -> f32) {
+	let pixels = rasterize(shape);
+	let path = golden_path(name);
+	if std::env::var_os("UPDATE_GOLDEN").is_some() {
+		write_png(&path, &pixels);
+		return;
+	}
+
+	let expected = read_png(&path);
+	let mismatches = pixels
+		.iter()
+		.zip(&expected)
+		.filter(|(a, b)| a.abs_diff(**b) > TOLERANCE)
+		.count();
+	assert_eq!(
+		mismatches, 0,
+		"{name} differs from its reference in {mismatches} pixels"
+	);
+}
+
+const CENTER: Vec2 = Vec2::new(0.5, 0.5);
+
+#[test]
+fn golden_rect() {
+	check("rect", |st| rect(vec2(0.6, 0.4), CENTER, st));
+	check("rect_smooth", |st| {
+		rect_smooth(vec2(0.6, 0.4), CENTER, st, 0.1)
+	});
+}
+
+#[test]
+fn golden_circle() {
+	check("circle", |st| circle(CENTER, 0.35, st));
+	check("circle_smooth", |st| circle_smooth(CENTER, 0.35, st, 0.08));
+}
+
+#[test]
+fn golden_rounded_rect() {
+	check("rounded_rect", |st| {
+		rounded_rect(st, CENTER, vec2(0.7, 0.5), 0.15)
+	});
+	check("rounded_rect_smooth", |st| {
+		rounded_rect_smooth(st, CENTER, vec2(0.7, 0.5), 0.15, 0.1)
+	});
+}
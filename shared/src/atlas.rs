@@ -0,0 +1,303 @@
+use trivalibs::prelude::*;
+
+/// Sub-rectangle of an atlas in texels, without the padding around it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtlasRect {
+	pub x: u32,
+	pub y: u32,
+	pub width: u32,
+	pub height: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct SkylineNode {
+	x: u32,
+	y: u32,
+	width: u32,
+}
+
+/// Skyline bottom-left rectangle packer. Every rect keeps `padding` texels of free space
+/// on each side, so mip levels sample less of the neighbouring surfaces. For `n` mip
+/// levels a padding of about `2^(n-1)` texels keeps the surfaces fully apart.
+pub struct AtlasPacker {
+	width: u32,
+	height: u32,
+	padding: u32,
+	skyline: Vec<SkylineNode>,
+}
+
+impl AtlasPacker {
+	pub fn new(width: u32, height: u32, padding: u32) -> Self {
+		Self {
+			width,
+			height,
+			padding,
+			skyline: vec![SkylineNode { x: 0, y: 0, width }],
+		}
+	}
+
+	// Lowest y at which a rect of `width` fits when its left edge is at node `i`.
+	fn fit(&self, i: usize, width: u32, height: u32) -> Option<u32> {
+		let x = self.skyline[i].x;
+		if x + width > self.width {
+			return None;
+		}
+		let mut y = 0;
+		let mut covered = 0;
+		for node in &self.skyline[i..] {
+			if covered >= width {
+				break;
+			}
+			y = y.max(node.y);
+			covered += node.width;
+		}
+		(y + height <= self.height).then_some(y)
+	}
+
+	/// Places a rect of the given size, or returns `None` if it does not fit anymore.
+	pub fn pack(&mut self, width: u32, height: u32) -> Option<AtlasRect> {
+		let w = width + 2 * self.padding;
+		let h = height + 2 * self.padding;
+
+		let mut best: Option<(usize, u32)> = None;
+		for i in 0..self.skyline.len() {
+			if let Some(y) = self.fit(i, w, h)
+				&& best.is_none_or(|(_, best_y)| y < best_y)
+			{
+				best = Some((i, y));
+			}
+		}
+		let (i, y) = best?;
+		let x = self.skyline[i].x;
+
+		self.skyline.insert(
+			i,
+			SkylineNode {
+				x,
+				y: y + h,
+				width: w,
+			},
+		);
+
+		// shrink or drop the nodes now covered by the new one
+		let right = x + w;
+		let mut j = i + 1;
+		while j < self.skyline.len() && self.skyline[j].x < right {
+			let node = &mut self.skyline[j];
+			let node_right = node.x + node.width;
+			if node_right <= right {
+				self.skyline.remove(j);
+			} else {
+				node.width = node_right - right;
+				node.x = right;
+				j += 1;
+			}
+		}
+
+		// merge neighbours of equal height
+		let mut k = 0;
+		while k + 1 < self.skyline.len() {
+			if self.skyline[k].y == self.skyline[k + 1].y {
+				self.skyline[k].width += self.skyline[k + 1].width;
+				self.skyline.remove(k + 1);
+			} else {
+				k += 1;
+			}
+		}
+
+		Some(AtlasRect {
+			x: x + self.padding,
+			y: y + self.padding,
+			width,
+			height,
+		})
+	}
+
+	/// Height of the highest packed rect including its padding.
+	pub fn used_height(&self) -> u32 {
+		self.skyline.iter().map(|n| n.y).max().unwrap_or(0)
+	}
+}
+
+/// Packed atlas layout. `rects` are in the order of the sizes passed to `Atlas::pack`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Atlas {
+	pub width: u32,
+	pub height: u32,
+	pub padding: u32,
+	pub rects: Vec<AtlasRect>,
+}
+
+impl Atlas {
+	/// Packs all sizes, tallest first, into the smallest atlas found among power of two
+	/// widths up to `max_size`. The height is cropped to the used space.
+	pub fn pack(sizes: &[(u32, u32)], max_size: u32, padding: u32) -> Option<Self> {
+		let mut order: Vec<usize> = (0..sizes.len()).collect();
+		order.sort_by_key(|i| std::cmp::Reverse((sizes[*i].1, sizes[*i].0)));
+
+		let min_width = sizes.iter().map(|s| s.0 + 2 * padding).max().unwrap_or(1);
+		let mut best: Option<Self> = None;
+		let mut width = min_width.next_power_of_two();
+
+		while width <= max_size {
+			let mut packer = AtlasPacker::new(width, max_size, padding);
+			let mut rects = vec![
+				AtlasRect {
+					x: 0,
+					y: 0,
+					width: 0,
+					height: 0
+				};
+				sizes.len()
+			];
+			let packed = order.iter().all(|&i| {
+				packer
+					.pack(sizes[i].0, sizes[i].1)
+					.map(|r| rects[i] = r)
+					.is_some()
+			});
+
+			if packed {
+				let atlas = Self {
+					width,
+					height: packer.used_height().max(1),
+					padding,
+					rects,
+				};
+				if best
+					.as_ref()
+					.is_none_or(|b| atlas.width * atlas.height < b.width * b.height)
+				{
+					best = Some(atlas);
+				}
+			}
+			width *= 2;
+		}
+		best
+	}
+
+	/// Maps a uv in [0, 1] of surface `index` into atlas uv space.
+	pub fn remap_uv(&self, index: usize, uv: Vec2) -> Vec2 {
+		let r = self.rects[index];
+		let size = vec2(self.width as f32, self.height as f32);
+		let min = vec2(r.x as f32, r.y as f32) / size;
+		let extent = vec2(r.width as f32, r.height as f32) / size;
+		min + uv * extent
+	}
+
+	pub fn remap_uvs(&self, index: usize, uvs: &mut [Vec2]) {
+		for uv in uvs {
+			*uv = self.remap_uv(index, *uv);
+		}
+	}
+
+	/// Fills the padding around every rect of RGBA data with the nearest edge texel, so
+	/// filtering and mip levels do not pick up the empty background.
+	pub fn bleed_padding<T: Copy>(&self, rgba: &mut [T]) {
+		let w = self.width as i64;
+		let h = self.height as i64;
+		let p = self.padding as i64;
+		for r in &self.rects {
+			if r.width == 0 || r.height == 0 {
+				continue;
+			}
+			let (x0, y0) = (r.x as i64, r.y as i64);
+			let (x1, y1) = (x0 + r.width as i64 - 1, y0 + r.height as i64 - 1);
+			for y in (y0 - p).max(0)..=(y1 + p).min(h - 1) {
+				for x in (x0 - p).max(0)..=(x1 + p).min(w - 1) {
+					if (x0..=x1).contains(&x) && (y0..=y1).contains(&y) {
+						continue;
+					}
+					let src = (y.clamp(y0, y1) * w + x.clamp(x0, x1)) as usize * 4;
+					let dst = (y * w + x) as usize * 4;
+					rgba.copy_within(src..src + 4, dst);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn overlaps(a: &AtlasRect, b: &AtlasRect, padding: u32) -> bool {
+		a.x < b.x + b.width + 2 * padding
+			&& b.x < a.x + a.width + 2 * padding
+			&& a.y < b.y + b.height + 2 * padding
+			&& b.y < a.y + a.height + 2 * padding
+	}
+
+	#[test]
+	fn rects_do_not_overlap() {
+		let sizes: Vec<(u32, u32)> = (0..40)
+			.map(|i| (8 + (i * 37) % 50, 4 + (i * 53) % 30))
+			.collect();
+		let padding = 2;
+		let atlas = Atlas::pack(&sizes, 1024, padding).unwrap();
+
+		for (i, a) in atlas.rects.iter().enumerate() {
+			assert_eq!((a.width, a.height), sizes[i]);
+			assert!(a.x >= padding && a.y >= padding);
+			assert!(a.x + a.width + padding <= atlas.width);
+			assert!(a.y + a.height + padding <= atlas.height);
+			for b in &atlas.rects[i + 1..] {
+				assert!(!overlaps(a, b, padding), "{a:?} {b:?}");
+			}
+		}
+
+		let used: u32 = sizes.iter().map(|(w, h)| w * h).sum();
+		assert!(used as f32 / (atlas.width * atlas.height) as f32 > 0.5);
+	}
+
+	#[test]
+	fn fails_when_too_small() {
+		assert!(Atlas::pack(&[(100, 10)], 64, 0).is_none());
+		let mut packer = AtlasPacker::new(16, 16, 0);
+		assert!(packer.pack(16, 8).is_some());
+		assert!(packer.pack(16, 8).is_some());
+		assert!(packer.pack(1, 1).is_none());
+	}
+
+	#[test]
+	fn remaps_uvs() {
+		let atlas = Atlas::pack(&[(64, 32), (32, 32)], 256, 4).unwrap();
+		let r = atlas.rects[1];
+		let size = vec2(atlas.width as f32, atlas.height as f32);
+		assert_eq!(
+			atlas.remap_uv(1, Vec2::ZERO) * size,
+			vec2(r.x as f32, r.y as f32)
+		);
+		let mut uvs = [Vec2::ONE];
+		atlas.remap_uvs(1, &mut uvs);
+		assert_eq!(
+			uvs[0] * size,
+			vec2((r.x + r.width) as f32, (r.y + r.height) as f32)
+		);
+	}
+
+	#[test]
+	fn bleeds_edges_into_padding() {
+		let atlas = Atlas {
+			width: 4,
+			height: 4,
+			padding: 1,
+			rects: vec![AtlasRect {
+				x: 1,
+				y: 1,
+				width: 2,
+				height: 2,
+			}],
+		};
+		let mut rgba = vec![0u8; 4 * 4 * 4];
+		for (i, v) in [(5, 1), (6, 2), (9, 3), (10, 4)] {
+			rgba[i * 4..i * 4 + 4].fill(v);
+		}
+		atlas.bleed_padding(&mut rgba);
+		let texel = |x: usize, y: usize| rgba[(y * 4 + x) * 4];
+		assert_eq!(texel(0, 0), 1);
+		assert_eq!(texel(3, 0), 2);
+		assert_eq!(texel(0, 3), 3);
+		assert_eq!(texel(3, 2), 4);
+	}
+}
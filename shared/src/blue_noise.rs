@@ -0,0 +1,306 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+const SIGMA: f32 = 1.5;
+const KERNEL_RADIUS: i32 = 6;
+// fraction of pixels set in the initial binary pattern
+const INITIAL_DENSITY: f32 = 0.1;
+
+// Gaussian energy of a set of points on a torus, updated incrementally.
+struct Energy {
+	width: usize,
+	height: usize,
+	kernel: Vec<(usize, usize, f32)>,
+	values: Vec<f32>,
+}
+
+impl Energy {
+	fn new(width: usize, height: usize) -> Self {
+		let mut kernel = Vec::new();
+		for dy in 0..height {
+			let wy = dy.min(height - dy) as i32;
+			if wy > KERNEL_RADIUS {
+				continue;
+			}
+			for dx in 0..width {
+				let wx = dx.min(width - dx) as i32;
+				if wx > KERNEL_RADIUS {
+					continue;
+				}
+				let d2 = (wx * wx + wy * wy) as f32;
+				kernel.push((dx, dy, (-d2 / (2.0 * SIGMA * SIGMA)).exp()));
+			}
+		}
+		Self {
+			width,
+			height,
+			kernel,
+			values: vec![0.0; width * height],
+		}
+	}
+
+	fn splat(&mut self, i: usize, sign: f32) {
+		let x = i % self.width;
+		let y = i / self.width;
+		for &(dx, dy, w) in &self.kernel {
+			let nx = (x + dx) % self.width;
+			let ny = (y + dy) % self.height;
+			self.values[ny * self.width + nx] += w * sign;
+		}
+	}
+
+	fn tightest_cluster(&self, pattern: &[bool]) -> usize {
+		let mut best = 0;
+		let mut best_e = f32::MIN;
+		for (i, &set) in pattern.iter().enumerate() {
+			if set && self.values[i] > best_e {
+				best_e = self.values[i];
+				best = i;
+			}
+		}
+		best
+	}
+
+	fn largest_void(&self, pattern: &[bool]) -> usize {
+		let mut best = 0;
+		let mut best_e = f32::MAX;
+		for (i, &set) in pattern.iter().enumerate() {
+			if !set && self.values[i] < best_e {
+				best_e = self.values[i];
+				best = i;
+			}
+		}
+		best
+	}
+}
+
+/// Void-and-cluster dither array. Returns the rank of every pixel, a permutation of
+/// `0..width * height`. The pattern is tileable, ranks are spread as evenly as possible
+/// at every threshold.
+///
+/// Every step scans all pixels, so the cost is quadratic in the pixel count. In release
+/// builds 64x64 takes milliseconds, 128x128 about a second and 256x256 around 20 seconds.
+/// Generate at most 128x128 and tile it, or cache larger arrays.
+pub fn blue_noise_ranks(width: u32, height: u32, seed: u64) -> Vec<u32> {
+	let w = width as usize;
+	let h = height as usize;
+	let n = w * h;
+	if n == 0 {
+		return vec![];
+	}
+	let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+	// initial random binary pattern
+	let initial_count = ((n as f32 * INITIAL_DENSITY) as usize).clamp(1, n);
+	let mut pattern = vec![false; n];
+	let mut energy = Energy::new(w, h);
+	let mut count = 0;
+	while count < initial_count {
+		let i = rng.random_range(0..n);
+		if !pattern[i] {
+			pattern[i] = true;
+			energy.splat(i, 1.0);
+			count += 1;
+		}
+	}
+
+	// relax clusters into voids until stable
+	if count < n {
+		loop {
+			let cluster = energy.tightest_cluster(&pattern);
+			pattern[cluster] = false;
+			energy.splat(cluster, -1.0);
+
+			let void = energy.largest_void(&pattern);
+			pattern[void] = true;
+			energy.splat(void, 1.0);
+
+			if void == cluster {
+				break;
+			}
+		}
+	}
+
+	let mut ranks = vec![0; n];
+
+	// phase 1: remove points from the prototype, densest first
+	let prototype = pattern.clone();
+	let prototype_energy = energy.values.clone();
+	for rank in (0..count).rev() {
+		let cluster = energy.tightest_cluster(&pattern);
+		pattern[cluster] = false;
+		energy.splat(cluster, -1.0);
+		ranks[cluster] = rank as u32;
+	}
+
+	// phase 2 and 3: fill the largest voids. With a toroidal kernel the tightest
+	// cluster of zeros is the largest void of ones, so one loop covers both phases.
+	pattern = prototype;
+	energy.values = prototype_energy;
+	for rank in count..n {
+		let void = energy.largest_void(&pattern);
+		pattern[void] = true;
+		energy.splat(void, 1.0);
+		ranks[void] = rank as u32;
+	}
+
+	ranks
+}
+
+/// Single channel blue noise with values evenly distributed in [0, 1).
+pub fn blue_noise_f32(width: u32, height: u32, seed: u64) -> Vec<f32> {
+	let n = (width * height) as f32;
+	blue_noise_ranks(width, height, seed)
+		.into_iter()
+		.map(|r| r as f32 / n)
+		.collect()
+}
+
+pub fn blue_noise_u8(width: u32, height: u32, seed: u64) -> Vec<u8> {
+	let n = (width * height) as u64;
+	blue_noise_ranks(width, height, seed)
+		.into_iter()
+		.map(|r| (r as u64 * 256 / n) as u8)
+		.collect()
+}
+
+fn interleave<T: Copy + Default>(channels: [Vec<T>; 4]) -> Vec<T> {
+	let mut rgba = vec![T::default(); channels[0].len() * 4];
+	for (c, channel) in channels.iter().enumerate() {
+		for (i, v) in channel.iter().enumerate() {
+			rgba[i * 4 + c] = *v;
+		}
+	}
+	rgba
+}
+
+// Output number `channel + 1` of a SplitMix64 generator seeded with `seed`. With
+// `seed + c` the green channel of seed n would be the red channel of seed n + 1.
+fn channel_seed(seed: u64, channel: u64) -> u64 {
+	let mut z = seed.wrapping_add((channel + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+	z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+	z ^ (z >> 31)
+}
+
+/// Four independent blue noise channels, each derived from `seed`.
+pub fn blue_noise_rgba_u8(width: u32, height: u32, seed: u64) -> Vec<u8> {
+	interleave([0, 1, 2, 3].map(|c| blue_noise_u8(width, height, channel_seed(seed, c))))
+}
+
+pub fn blue_noise_rgba_f32(width: u32, height: u32, seed: u64) -> Vec<f32> {
+	interleave([0, 1, 2, 3].map(|c| blue_noise_f32(width, height, channel_seed(seed, c))))
+}
+
+pub fn blue_noise_texture_u8(p: &mut Painter, width: u32, height: u32, seed: u64) -> Layer {
+	p.layer()
+		.with_size(width, height)
+		.with_format(wgpu::TextureFormat::Rgba8Unorm)
+		.with_static_texture_data(&blue_noise_rgba_u8(width, height, seed))
+		.create()
+}
+
+pub fn blue_noise_texture_f32(p: &mut Painter, width: u32, height: u32, seed: u64) -> Layer {
+	p.layer()
+		.with_size(width, height)
+		.with_format(wgpu::TextureFormat::Rgba32Float)
+		.with_static_texture_data(bytemuck::cast_slice(&blue_noise_rgba_f32(
+			width, height, seed,
+		)))
+		.create()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Radially averaged power of the low and high frequency bands.
+	fn band_power(values: &[f32], size: usize) -> (f32, f32) {
+		let mean = values.iter().sum::<f32>() / values.len() as f32;
+		let (mut low, mut low_n, mut high, mut high_n) = (0.0, 0, 0.0, 0);
+		for v in 0..size {
+			for u in 0..size {
+				let (mut re, mut im) = (0.0, 0.0);
+				for y in 0..size {
+					for x in 0..size {
+						let a = std::f32::consts::TAU * (u * x + v * y) as f32 / size as f32;
+						let s = values[y * size + x] - mean;
+						re += s * a.cos();
+						im -= s * a.sin();
+					}
+				}
+				let fu = u.min(size - u) as f32;
+				let fv = v.min(size - v) as f32;
+				let r = (fu * fu + fv * fv).sqrt();
+				let power = re * re + im * im;
+				if r > 0.0 && r < size as f32 / 8.0 {
+					low += power;
+					low_n += 1;
+				} else if r >= size as f32 / 4.0 {
+					high += power;
+					high_n += 1;
+				}
+			}
+		}
+		(low / low_n as f32, high / high_n as f32)
+	}
+
+	#[test]
+	fn ranks_are_a_permutation() {
+		let mut ranks = blue_noise_ranks(16, 8, 3);
+		ranks.sort();
+		assert_eq!(ranks, (0..128).collect::<Vec<_>>());
+	}
+
+	#[test]
+	fn empty_size() {
+		assert!(blue_noise_ranks(0, 16, 1).is_empty());
+		assert!(blue_noise_rgba_f32(16, 0, 1).is_empty());
+	}
+
+	#[test]
+	fn deterministic_from_seed() {
+		assert_eq!(blue_noise_u8(16, 16, 42), blue_noise_u8(16, 16, 42));
+		assert_ne!(blue_noise_u8(16, 16, 42), blue_noise_u8(16, 16, 43));
+	}
+
+	#[test]
+	fn spectrum_lacks_low_frequencies() {
+		let size = 32;
+		let noise = blue_noise_f32(size, size, 7);
+		let (low, high) = band_power(&noise, size as usize);
+		assert!(low < high * 0.2, "low {low} high {high}");
+
+		// every threshold is blue too, not only the full ramp
+		let half: Vec<f32> = noise.iter().map(|v| (*v < 0.5) as u8 as f32).collect();
+		let (low, high) = band_power(&half, size as usize);
+		assert!(low < high * 0.2, "low {low} high {high}");
+	}
+
+	#[test]
+	fn rgba_channels_differ() {
+		let rgba = blue_noise_rgba_u8(8, 8, 1);
+		assert_eq!(rgba.len(), 8 * 8 * 4);
+		let r: Vec<u8> = rgba.iter().step_by(4).copied().collect();
+		let g: Vec<u8> = rgba.iter().skip(1).step_by(4).copied().collect();
+		assert_ne!(r, g);
+	}
+
+	#[test]
+	fn channel_seeds_are_distinct() {
+		let seeds: Vec<u64> = (0..4)
+			.flat_map(|seed| (0..4).map(move |c| channel_seed(seed, c)))
+			.collect();
+		for (i, a) in seeds.iter().enumerate() {
+			assert!(!seeds[i + 1..].contains(a), "seed {} repeats", i / 4);
+		}
+
+		// the green channel of one seed is not the red channel of the next
+		let a = blue_noise_rgba_u8(8, 8, 5);
+		let b = blue_noise_rgba_u8(8, 8, 6);
+		let g: Vec<u8> = a.iter().skip(1).step_by(4).copied().collect();
+		let r: Vec<u8> = b.iter().step_by(4).copied().collect();
+		assert_ne!(g, r);
+	}
+}
@@ -0,0 +1,174 @@
+pub use shared_nostd::ShaderFn;
+use shared_nostd::color::linear_to_srgb;
+use std::{fs, io, path::Path, thread};
+use trivalibs::prelude::*;
+
+/// Linear RGBA pixels of a shader evaluated on the CPU, the first row is the top of the
+/// canvas like in the fragment shader.
+pub struct CpuImage {
+	pub width: u32,
+	pub height: u32,
+	pub pixels: Vec<Vec4>,
+}
+
+/// Evaluates `shader` for the center of every pixel, with the same `uv`, `size` and `time`
+/// inputs a fragment shader gets on the canvas. Rows are split across all cores.
+pub fn render<F>(width: u32, height: u32, time: f32, shader: F) -> CpuImage
+where
+	F: Fn(Vec2, UVec2, f32) -> Vec4 + Sync,
+{
+	let size = uvec2(width, height);
+	let mut pixels = vec![Vec4::ZERO; (width * height) as usize];
+
+	let threads = thread::available_parallelism().map_or(1, |n| n.get());
+	let rows_per_chunk = (height as usize).div_ceil(threads).max(1);
+	let shader = &shader;
+
+	if width > 0 {
+		thread::scope(|s| {
+			for (chunk_i, chunk) in pixels
+				.chunks_mut(rows_per_chunk * width as usize)
+				.enumerate()
+			{
+				s.spawn(move || {
+					let first_row = chunk_i * rows_per_chunk;
+					for (i, pixel) in chunk.iter_mut().enumerate() {
+						let x = i % width as usize;
+						let y = first_row + i / width as usize;
+						let uv = (vec2(x as f32, y as f32) + 0.5) / size.as_vec2();
+						*pixel = shader(uv, size, time);
+					}
+				});
+			}
+		});
+	}
+
+	CpuImage {
+		width,
+		height,
+		pixels,
+	}
+}
+
+fn to_u8(v: f32) -> u8 {
+	(v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+impl CpuImage {
+	pub fn pixel(&self, x: u32, y: u32) -> Vec4 {
+		self.pixels[(y * self.width + x) as usize]
+	}
+
+	/// 8 bit RGBA with the colors encoded to sRGB, which matches what the sRGB canvas
+	/// surface shows. Alpha stays linear.
+	pub fn to_rgba_u8(&self) -> Vec<u8> {
+		self
+			.pixels
+			.iter()
+			.flat_map(|c| {
+				[
+					to_u8(linear_to_srgb(c.x.max(0.0))),
+					to_u8(linear_to_srgb(c.y.max(0.0))),
+					to_u8(linear_to_srgb(c.z.max(0.0))),
+					to_u8(c.w),
+				]
+			})
+			.collect()
+	}
+
+	/// 8 bit RGBA without any encoding, for data textures.
+	pub fn to_rgba_u8_linear(&self) -> Vec<u8> {
+		self
+			.pixels
+			.iter()
+			.flat_map(|c| c.to_array().map(to_u8))
+			.collect()
+	}
+
+	/// Writes the sRGB encoded image as PNG.
+	pub fn write_png(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		write_png_rgba(path, self.width, self.height, &self.to_rgba_u8())
+	}
+}
+
+pub fn write_png_rgba(
+	path: impl AsRef<Path>,
+	width: u32,
+	height: u32,
+	rgba: &[u8],
+) -> io::Result<()> {
+	let path = path.as_ref();
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+	let file = io::BufWriter::new(fs::File::create(path)?);
+	let mut encoder = png::Encoder::new(file, width, height);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+	let mut writer = encoder.write_header().map_err(io::Error::other)?;
+	writer.write_image_data(rgba).map_err(io::Error::other)
+}
+
+/// Reads an 8 bit RGBA PNG, as written by `write_png_rgba`.
+pub fn read_png_rgba(path: impl AsRef<Path>) -> io::Result<(u32, u32, Vec<u8>)> {
+	let bytes = fs::read(path)?;
+	let mut reader = png::Decoder::new(io::Cursor::new(bytes))
+		.read_info()
+		.map_err(io::Error::other)?;
+	let mut buf = vec![0; reader.output_buffer_size().unwrap_or(0)];
+	let info = reader.next_frame(&mut buf).map_err(io::Error::other)?;
+	if info.color_type != png::ColorType::Rgba || info.bit_depth != png::BitDepth::Eight {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"expected an 8 bit RGBA PNG",
+		));
+	}
+	buf.truncate(info.buffer_size());
+	Ok((info.width, info.height, buf))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn evaluates_pixel_centers() {
+		let img = render(4, 2, 1.5, |uv, size, time| {
+			vec4(uv.x, uv.y, size.x as f32 + size.y as f32, time)
+		});
+		assert_eq!(img.pixels.len(), 8);
+		assert_eq!(img.pixel(0, 0), vec4(0.125, 0.25, 6.0, 1.5));
+		assert_eq!(img.pixel(3, 1), vec4(0.875, 0.75, 6.0, 1.5));
+		assert_eq!(render(0, 3, 0.0, |_, _, _| Vec4::ONE).pixels.len(), 0);
+	}
+
+	#[test]
+	fn parallel_rows_match_serial_order() {
+		// odd sizes leave a shorter last chunk
+		let (w, h) = (37, 53);
+		let img = render(w, h, 0.0, |uv, size, _| {
+			let p = (uv * size.as_vec2()).floor();
+			vec4(p.x, p.y, 0.0, 1.0)
+		});
+		for y in 0..h {
+			for x in 0..w {
+				assert_eq!(img.pixel(x, y).truncate(), vec3(x as f32, y as f32, 0.0));
+			}
+		}
+	}
+
+	#[test]
+	fn encodes_and_writes_png() {
+		let img = render(3, 1, 0.0, |uv, _, _| vec4(uv.x - 1.0 / 6.0, 0.5, 2.0, 0.5));
+		let rgba = img.to_rgba_u8();
+		assert_eq!(&rgba[..4], &[0, 188, 255, 128]);
+		assert_eq!(&img.to_rgba_u8_linear()[..4], &[0, 128, 255, 128]);
+
+		let path = std::env::temp_dir().join("shared_cpu_render_test/out.png");
+		img.write_png(&path).unwrap();
+		let (w, h, data) = read_png_rgba(&path).unwrap();
+		assert_eq!((w, h), (3, 1));
+		assert_eq!(data, rgba);
+	}
+}
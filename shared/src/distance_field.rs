@@ -0,0 +1,201 @@
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+const INF: f32 = 1e20;
+
+// Felzenszwalb & Huttenlocher 1D squared distance transform of a sampled function.
+fn edt_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+	let mut k = 0;
+	v[0] = 0;
+	z[0] = f32::NEG_INFINITY;
+	z[1] = f32::INFINITY;
+
+	for q in 1..f.len() {
+		let fq = f[q] + (q * q) as f32;
+		let mut s;
+		loop {
+			let p = v[k];
+			s = (fq - (f[p] + (p * p) as f32)) / (2 * q - 2 * p) as f32;
+			if s > z[k] {
+				break;
+			}
+			k -= 1;
+		}
+		k += 1;
+		v[k] = q;
+		z[k] = s;
+		z[k + 1] = f32::INFINITY;
+	}
+
+	k = 0;
+	for (q, dq) in d.iter_mut().enumerate() {
+		while z[k + 1] < q as f32 {
+			k += 1;
+		}
+		let p = v[k];
+		let dp = q as f32 - p as f32;
+		*dq = dp * dp + f[p];
+	}
+}
+
+/// Exact squared euclidean distance from every pixel to the nearest pixel where `site`
+/// is true. Pixels are `INF` far away if there is no site at all.
+pub fn squared_distance_transform(width: u32, height: u32, site: &[bool]) -> Vec<f32> {
+	let w = width as usize;
+	let h = height as usize;
+	assert_eq!(site.len(), w * h);
+
+	let n = w.max(h);
+	let mut f = vec![0.0; n];
+	let mut d = vec![0.0; n];
+	let mut v = vec![0; n];
+	let mut z = vec![0.0; n + 1];
+
+	let mut grid: Vec<f32> = site.iter().map(|s| if *s { 0.0 } else { INF }).collect();
+
+	for x in 0..w {
+		for y in 0..h {
+			f[y] = grid[y * w + x];
+		}
+		edt_1d(&f[..h], &mut d[..h], &mut v, &mut z);
+		for y in 0..h {
+			grid[y * w + x] = d[y];
+		}
+	}
+
+	for y in 0..h {
+		f[..w].copy_from_slice(&grid[y * w..(y + 1) * w]);
+		edt_1d(&f[..w], &mut d[..w], &mut v, &mut z);
+		grid[y * w..(y + 1) * w].copy_from_slice(&d[..w]);
+	}
+
+	grid
+}
+
+/// Signed distance in pixels to the mask outline, negative inside. A pixel is inside
+/// when its mask value is at least 0.5. The outline lies half way between pixel centers.
+pub fn signed_distance_field(width: u32, height: u32, mask: &[f32]) -> Vec<f32> {
+	let inside: Vec<bool> = mask.iter().map(|m| *m >= 0.5).collect();
+	let outside: Vec<bool> = inside.iter().map(|i| !i).collect();
+
+	let to_inside = squared_distance_transform(width, height, &inside);
+	let to_outside = squared_distance_transform(width, height, &outside);
+
+	inside
+		.iter()
+		.enumerate()
+		.map(|(i, is_inside)| {
+			if *is_inside {
+				-(to_outside[i].sqrt() - 0.5)
+			} else {
+				to_inside[i].sqrt() - 0.5
+			}
+		})
+		.collect()
+}
+
+/// Alpha channel of RGBA u8 data as mask values in [0, 1].
+pub fn mask_from_alpha(rgba: &[u8]) -> Vec<f32> {
+	rgba
+		.iter()
+		.skip(3)
+		.step_by(4)
+		.map(|a| *a as f32 / 255.0)
+		.collect()
+}
+
+/// Maps distances to [0, 1] with 0.5 on the outline, 1.0 at `spread` pixels inside and
+/// 0.0 at `spread` pixels outside. Returned as RGBA with the value in every channel.
+pub fn encode_distance_field(distances: &[f32], spread: f32) -> Vec<u8> {
+	let mut rgba = Vec::with_capacity(distances.len() * 4);
+	for d in distances {
+		let v = (0.5 - d / (2.0 * spread)).clamp(0.0, 1.0);
+		let v = (v * 255.0).round() as u8;
+		rgba.extend_from_slice(&[v, v, v, v]);
+	}
+	rgba
+}
+
+pub fn distance_field_texture(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	mask: &[f32],
+	spread: f32,
+) -> Layer {
+	let distances = signed_distance_field(width, height, mask);
+	p.layer()
+		.with_size(width, height)
+		.with_format(wgpu::TextureFormat::Rgba8Unorm)
+		.with_static_texture_data(&encode_distance_field(&distances, spread))
+		.create()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn brute_force(width: usize, height: usize, site: &[bool]) -> Vec<f32> {
+		(0..width * height)
+			.map(|i| {
+				let (x, y) = ((i % width) as f32, (i / width) as f32);
+				site
+					.iter()
+					.enumerate()
+					.filter(|(_, s)| **s)
+					.map(|(j, _)| {
+						let (sx, sy) = ((j % width) as f32, (j / width) as f32);
+						(x - sx).powi(2) + (y - sy).powi(2)
+					})
+					.fold(INF, f32::min)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn matches_brute_force() {
+		let (w, h) = (23, 17);
+		// deterministic scattered sites
+		let site: Vec<bool> = (0..w * h).map(|i| (i * 7919 + 13) % 37 == 0).collect();
+		let fast = squared_distance_transform(w as u32, h as u32, &site);
+		let slow = brute_force(w, h, &site);
+		assert_eq!(fast, slow);
+	}
+
+	#[test]
+	fn empty_mask_is_far() {
+		let d = squared_distance_transform(4, 4, &[false; 16]);
+		assert!(d.iter().all(|v| *v >= INF));
+	}
+
+	#[test]
+	fn disc_distances() {
+		let (w, h) = (32, 32);
+		let radius = 8.0;
+		let mask: Vec<f32> = (0..w * h)
+			.map(|i| {
+				let x = (i % w) as f32 - 15.5;
+				let y = (i / w) as f32 - 15.5;
+				((x * x + y * y).sqrt() < radius) as u8 as f32
+			})
+			.collect();
+		let sdf = signed_distance_field(w as u32, h as u32, &mask);
+		for (i, d) in sdf.iter().enumerate() {
+			let x = (i % w) as f32 - 15.5;
+			let y = (i / w) as f32 - 15.5;
+			let exact = (x * x + y * y).sqrt() - radius;
+			// pixel quantization of the outline
+			assert!((d - exact).abs() < 1.0, "{d} vs {exact}");
+		}
+		assert!(sdf[16 * w + 16] < 0.0);
+		assert!(sdf[0] > 0.0);
+	}
+
+	#[test]
+	fn encoding_spread() {
+		let rgba = encode_distance_field(&[-4.0, 0.0, 4.0, 100.0], 4.0);
+		assert_eq!(rgba[0], 255);
+		assert_eq!(rgba[4], 128);
+		assert_eq!(rgba[8], 0);
+		assert_eq!(rgba[12], 0);
+	}
+}
@@ -0,0 +1,276 @@
+use std::fmt;
+use trivalibs::{
+	painter::{
+		Painter,
+		binding::{LayerBinding, ValueBinding},
+		layer::Layer,
+		prelude::*,
+		shade::Shade,
+		wgpu,
+	},
+	prelude::*,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+	Vertex,
+	Fragment,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UniformKind {
+	Buffer,
+	Sampler,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectLayoutError {
+	/// Two uniforms were assigned the same binding index in descriptor set 0.
+	DuplicateBinding(u32),
+	/// Two layers were assigned the same binding index in descriptor set 1.
+	DuplicateLayer(u32),
+	/// Binding indices must be contiguous from 0, this one is not used.
+	MissingBinding(u32),
+	MissingLayer(u32),
+}
+
+impl fmt::Display for EffectLayoutError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::DuplicateBinding(i) => write!(f, "binding {i} is assigned more than once"),
+			Self::DuplicateLayer(i) => write!(f, "layer binding {i} is assigned more than once"),
+			Self::MissingBinding(i) => write!(
+				f,
+				"binding {i} is not assigned, binding indices must be contiguous from 0"
+			),
+			Self::MissingLayer(i) => write!(
+				f,
+				"layer binding {i} is not assigned, layer indices must be contiguous from 0"
+			),
+		}
+	}
+}
+
+impl std::error::Error for EffectLayoutError {}
+
+struct Uniform {
+	index: u32,
+	kind: UniformKind,
+	stage: ShaderStage,
+	binding: ValueBinding,
+}
+
+struct LayerInput {
+	index: u32,
+	stage: ShaderStage,
+	binding: LayerBinding,
+}
+
+/// Builds a static single effect layer from an explicit binding layout. Uniforms live in
+/// descriptor set 0, layers in set 1. The canvas size is only bound when requested with
+/// `with_size_binding`.
+///
+/// ```ignore
+/// let (layer, shade) = StaticEffectLayerBuilder::new(p, 256, 256)
+///     .with_size_binding(0)
+///     .with_buffer(1, u_color.binding())
+///     .with_sampler(2, sampler.binding())
+///     .with_layer(0, noise.binding())
+///     .create()?;
+/// ```
+pub struct StaticEffectLayerBuilder<'a> {
+	painter: &'a mut Painter,
+	width: u32,
+	height: u32,
+	format: wgpu::TextureFormat,
+	size_binding: Option<u32>,
+	uniforms: Vec<Uniform>,
+	layers: Vec<LayerInput>,
+}
+
+impl<'a> StaticEffectLayerBuilder<'a> {
+	pub fn new(painter: &'a mut Painter, width: u32, height: u32) -> Self {
+		Self {
+			painter,
+			width,
+			height,
+			format: wgpu::TextureFormat::Rgba8UnormSrgb,
+			size_binding: None,
+			uniforms: Vec::new(),
+			layers: Vec::new(),
+		}
+	}
+
+	pub fn with_format(mut self, format: wgpu::TextureFormat) -> Self {
+		self.format = format;
+		self
+	}
+
+	/// Binds the layer size as a `UVec2` fragment uniform at `index`.
+	pub fn with_size_binding(mut self, index: u32) -> Self {
+		self.size_binding = Some(index);
+		self
+	}
+
+	pub fn with_uniform(
+		mut self,
+		index: u32,
+		kind: UniformKind,
+		stage: ShaderStage,
+		binding: ValueBinding,
+	) -> Self {
+		self.uniforms.push(Uniform {
+			index,
+			kind,
+			stage,
+			binding,
+		});
+		self
+	}
+
+	pub fn with_buffer(self, index: u32, binding: ValueBinding) -> Self {
+		self.with_uniform(index, UniformKind::Buffer, ShaderStage::Fragment, binding)
+	}
+
+	pub fn with_sampler(self, index: u32, binding: ValueBinding) -> Self {
+		self.with_uniform(index, UniformKind::Sampler, ShaderStage::Fragment, binding)
+	}
+
+	pub fn with_layer_at_stage(
+		mut self,
+		index: u32,
+		stage: ShaderStage,
+		binding: LayerBinding,
+	) -> Self {
+		self.layers.push(LayerInput {
+			index,
+			stage,
+			binding,
+		});
+		self
+	}
+
+	pub fn with_layer(self, index: u32, binding: LayerBinding) -> Self {
+		self.with_layer_at_stage(index, ShaderStage::Fragment, binding)
+	}
+
+	/// Checks the layout before any GPU resources are created.
+	pub fn validate(&self) -> Result<(), EffectLayoutError> {
+		let mut uniform_indices: Vec<u32> = self.uniforms.iter().map(|u| u.index).collect();
+		uniform_indices.extend(self.size_binding);
+		check_indices(
+			uniform_indices,
+			EffectLayoutError::DuplicateBinding,
+			EffectLayoutError::MissingBinding,
+		)?;
+
+		let layer_indices = self.layers.iter().map(|l| l.index).collect();
+		check_indices(
+			layer_indices,
+			EffectLayoutError::DuplicateLayer,
+			EffectLayoutError::MissingLayer,
+		)
+	}
+
+	pub fn create(mut self) -> Result<(Layer, Shade), EffectLayoutError> {
+		self.validate()?;
+
+		let p = self.painter;
+
+		if let Some(index) = self.size_binding {
+			let size = p.bind_const_uvec2(uvec2(self.width, self.height));
+			self.uniforms.push(Uniform {
+				index,
+				kind: UniformKind::Buffer,
+				stage: ShaderStage::Fragment,
+				binding: size,
+			});
+		}
+
+		self.uniforms.sort_by_key(|u| u.index);
+		self.layers.sort_by_key(|l| l.index);
+
+		let binding_layouts: Vec<_> = self
+			.uniforms
+			.iter()
+			.map(|u| match (u.kind, u.stage) {
+				(UniformKind::Buffer, ShaderStage::Fragment) => BINDING_BUFFER_FRAG,
+				(UniformKind::Buffer, ShaderStage::Vertex) => BINDING_BUFFER_VERT,
+				(UniformKind::Sampler, ShaderStage::Fragment) => BINDING_SAMPLER_FRAG,
+				(UniformKind::Sampler, ShaderStage::Vertex) => BINDING_SAMPLER_VERT,
+			})
+			.collect();
+
+		let layer_layouts: Vec<_> = self
+			.layers
+			.iter()
+			.map(|l| match l.stage {
+				ShaderStage::Fragment => BINDING_LAYER_FRAG,
+				ShaderStage::Vertex => BINDING_LAYER_VERT,
+			})
+			.collect();
+
+		let shade = p
+			.shade_effect()
+			.with_bindings(binding_layouts)
+			.with_layers(layer_layouts)
+			.create();
+
+		let bindings: Vec<_> = self.uniforms.iter().map(|u| (u.index, u.binding)).collect();
+		let layers: Vec<_> = self.layers.iter().map(|l| (l.index, l.binding)).collect();
+
+		let layer = p
+			.single_effect_layer(shade)
+			.with_size(self.width, self.height)
+			.with_bindings(bindings)
+			.with_layers(layers)
+			.with_format(self.format)
+			.create();
+
+		Ok((layer, shade))
+	}
+}
+
+fn check_indices(
+	mut indices: Vec<u32>,
+	duplicate: fn(u32) -> EffectLayoutError,
+	missing: fn(u32) -> EffectLayoutError,
+) -> Result<(), EffectLayoutError> {
+	indices.sort();
+	for (i, index) in indices.iter().enumerate() {
+		if i > 0 && indices[i - 1] == *index {
+			return Err(duplicate(*index));
+		}
+	}
+	for (expected, index) in indices.iter().enumerate() {
+		if *index != expected as u32 {
+			return Err(missing(expected as u32));
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn check(indices: &[u32]) -> Result<(), EffectLayoutError> {
+		check_indices(
+			indices.to_vec(),
+			EffectLayoutError::DuplicateBinding,
+			EffectLayoutError::MissingBinding,
+		)
+	}
+
+	#[test]
+	fn index_validation() {
+		assert_eq!(check(&[]), Ok(()));
+		assert_eq!(check(&[2, 0, 1]), Ok(()));
+		assert_eq!(
+			check(&[0, 1, 1]),
+			Err(EffectLayoutError::DuplicateBinding(1))
+		);
+		assert_eq!(check(&[0, 2]), Err(EffectLayoutError::MissingBinding(1)));
+		assert_eq!(check(&[1]), Err(EffectLayoutError::MissingBinding(0)));
+	}
+}
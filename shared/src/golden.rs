@@ -0,0 +1,161 @@
+use crate::cpu_render::{CpuImage, read_png_rgba, write_png_rgba};
+use shared_nostd::color::{linear_to_oklab, srgb_to_linear};
+use std::path::Path;
+use trivalibs::prelude::*;
+
+/// Set this environment variable to rewrite the references instead of comparing.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tolerance {
+	/// Largest color difference of a pixel, as euclidean distance in Oklab. Around 0.02 is
+	/// barely noticeable side by side.
+	pub max_delta_e: f32,
+	/// Largest alpha difference of a pixel.
+	pub max_alpha: f32,
+	/// Fraction of pixels that may exceed the limits, for noise in high frequency areas.
+	pub max_bad_fraction: f32,
+}
+
+impl Default for Tolerance {
+	fn default() -> Self {
+		Self {
+			max_delta_e: 0.02,
+			max_alpha: 2.0 / 255.0,
+			max_bad_fraction: 0.001,
+		}
+	}
+}
+
+pub struct Comparison {
+	pub bad_pixels: usize,
+	pub max_delta_e: f32,
+	/// The reference in gray, with the pixels exceeding the tolerance in red.
+	pub diff_rgba: Vec<u8>,
+}
+
+fn oklab(rgba: &[u8]) -> Vec3 {
+	let c = vec3(rgba[0] as f32, rgba[1] as f32, rgba[2] as f32) / 255.0;
+	linear_to_oklab(vec3(
+		srgb_to_linear(c.x),
+		srgb_to_linear(c.y),
+		srgb_to_linear(c.z),
+	))
+}
+
+/// Compares two sRGB encoded RGBA images of the same size.
+pub fn compare_rgba(actual: &[u8], expected: &[u8], tolerance: Tolerance) -> Comparison {
+	assert_eq!(actual.len(), expected.len());
+	let mut bad_pixels = 0;
+	let mut max_delta_e: f32 = 0.0;
+	let mut diff_rgba = Vec::with_capacity(expected.len());
+
+	for (a, e) in actual.chunks_exact(4).zip(expected.chunks_exact(4)) {
+		let delta_e = oklab(a).distance(oklab(e));
+		let alpha = a[3].abs_diff(e[3]) as f32 / 255.0;
+		max_delta_e = max_delta_e.max(delta_e);
+
+		if delta_e > tolerance.max_delta_e || alpha > tolerance.max_alpha {
+			bad_pixels += 1;
+			diff_rgba.extend([255, 0, 0, 255]);
+		} else {
+			let gray = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 6) as u8;
+			diff_rgba.extend([gray, gray, gray, 255]);
+		}
+	}
+
+	Comparison {
+		bad_pixels,
+		max_delta_e,
+		diff_rgba,
+	}
+}
+
+/// Compares the image with `<dir>/<name>.png`. On failure the actual image and the diff
+/// are written to `diff_dir`. With `UPDATE_GOLDEN` set the reference is rewritten.
+pub fn check_golden(
+	image: &CpuImage,
+	name: &str,
+	dir: &Path,
+	diff_dir: &Path,
+	tolerance: Tolerance,
+) -> Result<(), String> {
+	let update = std::env::var_os(UPDATE_ENV).is_some();
+	check_golden_with(image, name, dir, diff_dir, tolerance, update)
+}
+
+/// Like `check_golden`, with `update` in place of the environment variable.
+pub fn check_golden_with(
+	image: &CpuImage,
+	name: &str,
+	dir: &Path,
+	diff_dir: &Path,
+	tolerance: Tolerance,
+	update: bool,
+) -> Result<(), String> {
+	let path = dir.join(format!("{name}.png"));
+	let actual = image.to_rgba_u8();
+
+	if update {
+		return write_png_rgba(&path, image.width, image.height, &actual)
+			.map_err(|e| format!("{name}: could not write {}: {e}", path.display()));
+	}
+
+	let (width, height, expected) = read_png_rgba(&path).map_err(|e| {
+		format!(
+			"{name}: could not read {}: {e}, run with {UPDATE_ENV}=1 to create it",
+			path.display()
+		)
+	})?;
+	if (width, height) != (image.width, image.height) {
+		return Err(format!(
+			"{name}: reference is {width}x{height}, rendered {}x{}",
+			image.width, image.height
+		));
+	}
+
+	let comparison = compare_rgba(&actual, &expected, tolerance);
+	let allowed = (tolerance.max_bad_fraction * actual.len() as f32 / 4.0) as usize;
+	if comparison.bad_pixels <= allowed {
+		return Ok(());
+	}
+
+	let actual_path = diff_dir.join(format!("{name}.actual.png"));
+	let diff_path = diff_dir.join(format!("{name}.diff.png"));
+	write_png_rgba(&actual_path, width, height, &actual)
+		.and_then(|_| write_png_rgba(&diff_path, width, height, &comparison.diff_rgba))
+		.map_err(|e| format!("{name}: could not write diff images: {e}"))?;
+
+	Err(format!(
+		"{name}: {} pixels differ (max delta E {:.3}), see {}",
+		comparison.bad_pixels,
+		comparison.max_delta_e,
+		diff_path.display()
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn perceptual_tolerance() {
+		let expected = [100, 150, 200, 255, 120, 120, 120, 255];
+		let slightly_off = [101, 149, 201, 255, 121, 120, 119, 255];
+		let c = compare_rgba(&slightly_off, &expected, Tolerance::default());
+		assert_eq!(c.bad_pixels, 0);
+		assert!(c.max_delta_e > 0.0);
+
+		let wrong_color = [100, 150, 200, 255, 120, 180, 120, 255];
+		let c = compare_rgba(&wrong_color, &expected, Tolerance::default());
+		assert_eq!(c.bad_pixels, 1);
+		assert_eq!(&c.diff_rgba[4..], &[255, 0, 0, 255]);
+		assert_eq!(&c.diff_rgba[..4], &[75, 75, 75, 255]);
+
+		let wrong_alpha = [100, 150, 200, 128, 120, 120, 120, 255];
+		assert_eq!(
+			compare_rgba(&wrong_alpha, &expected, Tolerance::default()).bad_pixels,
+			1
+		);
+	}
+}
@@ -0,0 +1,307 @@
+use shared_nostd::color::{
+	hsv_to_rgb, lerp_hue, linear_to_oklab, linear_to_srgb_rgb, oklab_to_linear, rgb_to_hsv,
+	srgb_to_linear_rgb,
+};
+use std::f32::consts::TAU;
+use trivalibs::{
+	painter::{Painter, layer::Layer},
+	prelude::*,
+};
+
+/// Color space in which neighbouring stops are blended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientSpace {
+	Srgb,
+	Linear,
+	Oklab,
+	/// Hue takes the shorter way around the color wheel.
+	Hsv,
+}
+
+/// Gradient stop with a position in [0, 1] and an sRGB color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorStop {
+	pub position: f32,
+	pub color: Vec3,
+}
+
+pub fn stop(position: f32, color: Vec3) -> ColorStop {
+	ColorStop { position, color }
+}
+
+fn to_space(c: Vec3, space: GradientSpace) -> Vec3 {
+	match space {
+		GradientSpace::Srgb => c,
+		GradientSpace::Linear => srgb_to_linear_rgb(c),
+		GradientSpace::Oklab => linear_to_oklab(srgb_to_linear_rgb(c)),
+		GradientSpace::Hsv => rgb_to_hsv(c),
+	}
+}
+
+fn from_space(c: Vec3, space: GradientSpace) -> Vec3 {
+	let rgb = match space {
+		GradientSpace::Srgb => c,
+		GradientSpace::Linear => linear_to_srgb_rgb(c),
+		GradientSpace::Oklab => linear_to_srgb_rgb(oklab_to_linear(c)),
+		GradientSpace::Hsv => hsv_to_rgb(c),
+	};
+	rgb.clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+fn mix(a: Vec3, b: Vec3, t: f32, space: GradientSpace) -> Vec3 {
+	if space == GradientSpace::Hsv {
+		let h = lerp_hue(a.x, b.x, t);
+		let sv = a.yz().lerp(b.yz(), t);
+		return vec3(h, sv.x, sv.y);
+	}
+	a.lerp(b, t)
+}
+
+/// Samples the gradient at `t` and returns an sRGB color. Stops do not need to be
+/// sorted, `t` outside of the stop range takes the color of the nearest stop.
+pub fn sample_gradient(stops: &[ColorStop], t: f32, space: GradientSpace) -> Vec3 {
+	assert!(!stops.is_empty(), "gradient needs at least one stop");
+	let mut sorted = stops.to_vec();
+	sorted.sort_by(|a, b| a.position.total_cmp(&b.position));
+	sample_sorted(&sorted, t, space)
+}
+
+fn sample_sorted(stops: &[ColorStop], t: f32, space: GradientSpace) -> Vec3 {
+	let first = stops[0];
+	let last = stops[stops.len() - 1];
+	if t <= first.position {
+		return first.color;
+	}
+	if t >= last.position {
+		return last.color;
+	}
+	let i = stops.iter().position(|s| s.position > t).unwrap();
+	let (a, b) = (stops[i - 1], stops[i]);
+	let local = (t - a.position) / (b.position - a.position);
+	from_space(
+		mix(
+			to_space(a.color, space),
+			to_space(b.color, space),
+			local,
+			space,
+		),
+		space,
+	)
+}
+
+fn push_rgba(rgba: &mut Vec<u8>, c: Vec3) {
+	let c = (c.clamp(Vec3::ZERO, Vec3::ONE) * 255.0).round();
+	rgba.extend_from_slice(&[c.x as u8, c.y as u8, c.z as u8, 255]);
+}
+
+/// One row of `width` texels, texel centers sampled from 0 to 1 inclusive.
+pub fn gradient_rgba_u8(width: u32, stops: &[ColorStop], space: GradientSpace) -> Vec<u8> {
+	gradient_2d_rgba_u8(width, 1, &[stops], space)
+}
+
+/// Blends between several gradients along the y axis, the first gradient is at the top
+/// row and the last one at the bottom. With `height == gradients.len()` every row holds
+/// exactly one gradient, which works as palette atlas.
+pub fn gradient_2d_rgba_u8(
+	width: u32,
+	height: u32,
+	gradients: &[&[ColorStop]],
+	space: GradientSpace,
+) -> Vec<u8> {
+	assert!(!gradients.is_empty(), "at least one gradient is required");
+	let sorted: Vec<Vec<ColorStop>> = gradients
+		.iter()
+		.map(|stops| {
+			let mut s = stops.to_vec();
+			s.sort_by(|a, b| a.position.total_cmp(&b.position));
+			s
+		})
+		.collect();
+
+	let coord = |i: u32, n: u32| {
+		if n > 1 {
+			i as f32 / (n - 1) as f32
+		} else {
+			0.0
+		}
+	};
+
+	let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+	for y in 0..height {
+		let v = coord(y, height) * (sorted.len() - 1) as f32;
+		let row = (v.floor() as usize).min(sorted.len() - 1);
+		let next = (row + 1).min(sorted.len() - 1);
+		let local = v - row as f32;
+
+		for x in 0..width {
+			let u = coord(x, width);
+			let a = sample_sorted(&sorted[row], u, space);
+			let b = sample_sorted(&sorted[next], u, space);
+			let c = from_space(
+				mix(to_space(a, space), to_space(b, space), local, space),
+				space,
+			);
+			push_rgba(&mut rgba, c);
+		}
+	}
+	rgba
+}
+
+/// Iñigo Quilez' cosine palette `a + b * cos(TAU * (c * t + d))`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CosinePalette {
+	pub a: Vec3,
+	pub b: Vec3,
+	pub c: Vec3,
+	pub d: Vec3,
+}
+
+impl CosinePalette {
+	pub const RAINBOW: Self = Self {
+		a: Vec3::splat(0.5),
+		b: Vec3::splat(0.5),
+		c: Vec3::ONE,
+		d: vec3(0.0, 0.33, 0.67),
+	};
+
+	pub fn color(&self, t: f32) -> Vec3 {
+		let arg = (self.c * t + self.d) * TAU;
+		self.a + self.b * vec3(arg.x.cos(), arg.y.cos(), arg.z.cos())
+	}
+}
+
+pub fn cosine_palette_rgba_u8(width: u32, palette: &CosinePalette) -> Vec<u8> {
+	let mut rgba = Vec::with_capacity((width * 4) as usize);
+	for x in 0..width {
+		let t = x as f32 / (width.max(2) - 1) as f32;
+		push_rgba(&mut rgba, palette.color(t));
+	}
+	rgba
+}
+
+pub fn gradient_texture(
+	p: &mut Painter,
+	width: u32,
+	stops: &[ColorStop],
+	space: GradientSpace,
+) -> Layer {
+	p.layer()
+		.with_size(width, 1)
+		.with_static_texture_data(&gradient_rgba_u8(width, stops, space))
+		.create()
+}
+
+pub fn gradient_texture_2d(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	gradients: &[&[ColorStop]],
+	space: GradientSpace,
+) -> Layer {
+	p.layer()
+		.with_size(width, height)
+		.with_static_texture_data(&gradient_2d_rgba_u8(width, height, gradients, space))
+		.create()
+}
+
+pub fn cosine_palette_texture(p: &mut Painter, width: u32, palette: &CosinePalette) -> Layer {
+	p.layer()
+		.with_size(width, 1)
+		.with_static_texture_data(&cosine_palette_rgba_u8(width, palette))
+		.create()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BLACK_WHITE: [ColorStop; 2] = [
+		ColorStop {
+			position: 0.0,
+			color: Vec3::ZERO,
+		},
+		ColorStop {
+			position: 1.0,
+			color: Vec3::ONE,
+		},
+	];
+
+	fn assert_close(a: Vec3, b: Vec3) {
+		assert!((a - b).abs().max_element() < 1e-3, "{a} != {b}");
+	}
+
+	#[test]
+	fn endpoints_and_clamping() {
+		for space in [
+			GradientSpace::Srgb,
+			GradientSpace::Linear,
+			GradientSpace::Oklab,
+			GradientSpace::Hsv,
+		] {
+			assert_close(sample_gradient(&BLACK_WHITE, -1.0, space), Vec3::ZERO);
+			assert_close(sample_gradient(&BLACK_WHITE, 0.0, space), Vec3::ZERO);
+			assert_close(sample_gradient(&BLACK_WHITE, 1.0, space), Vec3::ONE);
+			assert_close(sample_gradient(&BLACK_WHITE, 2.0, space), Vec3::ONE);
+		}
+	}
+
+	#[test]
+	fn blend_spaces() {
+		let srgb = sample_gradient(&BLACK_WHITE, 0.5, GradientSpace::Srgb);
+		assert_close(srgb, Vec3::splat(0.5));
+		// linear 0.5 is brighter once encoded
+		let linear = sample_gradient(&BLACK_WHITE, 0.5, GradientSpace::Linear);
+		assert_close(linear, Vec3::splat(0.7354));
+		// oklab lightness is perceptually uniform, gray stays neutral
+		let oklab = sample_gradient(&BLACK_WHITE, 0.5, GradientSpace::Oklab);
+		assert!((oklab.x - oklab.z).abs() < 1e-3);
+
+		// red to blue through magenta, not through green
+		let hsv = sample_gradient(
+			&[
+				stop(0.0, vec3(1.0, 0.0, 0.0)),
+				stop(1.0, vec3(0.0, 0.0, 1.0)),
+			],
+			0.5,
+			GradientSpace::Hsv,
+		);
+		assert_close(hsv, vec3(1.0, 0.0, 1.0));
+	}
+
+	#[test]
+	fn unsorted_stops() {
+		let stops = [
+			stop(1.0, Vec3::ONE),
+			stop(0.0, Vec3::ZERO),
+			stop(0.5, vec3(1.0, 0.0, 0.0)),
+		];
+		assert_close(
+			sample_gradient(&stops, 0.5, GradientSpace::Srgb),
+			vec3(1.0, 0.0, 0.0),
+		);
+		assert_close(
+			sample_gradient(&stops, 0.75, GradientSpace::Srgb),
+			vec3(1.0, 0.5, 0.5),
+		);
+	}
+
+	#[test]
+	fn textures() {
+		let row = gradient_rgba_u8(3, &BLACK_WHITE, GradientSpace::Srgb);
+		assert_eq!(
+			row,
+			vec![0, 0, 0, 255, 128, 128, 128, 255, 255, 255, 255, 255]
+		);
+
+		let red = [stop(0.0, vec3(1.0, 0.0, 0.0))];
+		let blue = [stop(0.0, vec3(0.0, 0.0, 1.0))];
+		let atlas = gradient_2d_rgba_u8(2, 3, &[&red, &blue], GradientSpace::Srgb);
+		assert_eq!(&atlas[0..4], &[255, 0, 0, 255]);
+		assert_eq!(&atlas[8..12], &[128, 0, 128, 255]);
+		assert_eq!(&atlas[16..20], &[0, 0, 255, 255]);
+
+		let palette = cosine_palette_rgba_u8(2, &CosinePalette::RAINBOW);
+		// t = 0 gives a + b * cos(TAU * d)
+		assert_eq!(palette[0], 255);
+	}
+}
@@ -0,0 +1,415 @@
+use std::{
+	fs,
+	io::{Error, ErrorKind, Result},
+	path::Path,
+};
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+const MIN_RLE_WIDTH: usize = 8;
+const MAX_RLE_WIDTH: usize = 0x7fff;
+const MAX_RUN: usize = 127;
+/// Largest value RGBE can represent, a mantissa of 255 with the largest exponent.
+const MAX_RGBE: f32 = 255.0 * (1u128 << 119) as f32;
+
+/// Decoded Radiance image as RGBA f32 pixels, alpha is always 1.0.
+pub struct HdrImage {
+	pub width: u32,
+	pub height: u32,
+	pub data: Vec<f32>,
+}
+
+/// Negative and NaN components become 0, infinite and too large ones the largest RGBE value.
+pub fn rgb_to_rgbe(r: f32, g: f32, b: f32) -> [u8; 4] {
+	let [r, g, b] = [r, g, b].map(|c| {
+		if c.is_nan() {
+			0.0
+		} else {
+			c.clamp(0.0, MAX_RGBE)
+		}
+	});
+	let v = r.max(g).max(b);
+	if v < 1e-32 {
+		return [0, 0, 0, 0];
+	}
+	let (m, e) = frexp(v);
+	let scale = m * 256.0 / v;
+	[
+		(r * scale) as u8,
+		(g * scale) as u8,
+		(b * scale) as u8,
+		(e + 128) as u8,
+	]
+}
+
+pub fn rgbe_to_rgb(rgbe: [u8; 4]) -> [f32; 3] {
+	if rgbe[3] == 0 {
+		return [0.0; 3];
+	}
+	let f = 2f32.powi(rgbe[3] as i32 - (128 + 8));
+	[
+		(rgbe[0] as f32 + 0.5) * f,
+		(rgbe[1] as f32 + 0.5) * f,
+		(rgbe[2] as f32 + 0.5) * f,
+	]
+}
+
+// Splits v into mantissa in [0.5, 1) and exponent, like C frexp.
+fn frexp(v: f32) -> (f32, i32) {
+	let mut e = v.log2().floor() as i32 + 1;
+	let mut m = v / 2f32.powi(e);
+	if m >= 1.0 {
+		m *= 0.5;
+		e += 1;
+	} else if m < 0.5 {
+		m *= 2.0;
+		e -= 1;
+	}
+	(m, e)
+}
+
+fn invalid(msg: &str) -> Error {
+	Error::new(ErrorKind::InvalidData, format!("hdr: {msg}"))
+}
+
+/// Encodes RGBA f32 pixels (alpha is dropped) as run length encoded Radiance data.
+pub fn encode_hdr(width: u32, height: u32, rgba: &[f32]) -> Vec<u8> {
+	let w = width as usize;
+	let h = height as usize;
+	assert_eq!(
+		rgba.len(),
+		w * h * 4,
+		"hdr: rgba buffer does not match size"
+	);
+
+	let mut out = Vec::with_capacity(w * h * 4 + 64);
+	out.extend_from_slice(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n");
+	out.extend_from_slice(format!("-Y {height} +X {width}\n").as_bytes());
+
+	let mut scanline = vec![[0u8; 4]; w];
+	for y in 0..h {
+		for (x, px) in scanline.iter_mut().enumerate() {
+			let i = (y * w + x) * 4;
+			*px = rgb_to_rgbe(rgba[i], rgba[i + 1], rgba[i + 2]);
+		}
+
+		if !(MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&w) {
+			for px in &scanline {
+				out.extend_from_slice(px);
+			}
+			continue;
+		}
+
+		out.extend_from_slice(&[2, 2, (w >> 8) as u8, (w & 0xff) as u8]);
+		let mut channel = vec![0u8; w];
+		for c in 0..4 {
+			for (v, px) in channel.iter_mut().zip(&scanline) {
+				*v = px[c];
+			}
+			write_rle_channel(&mut out, &channel);
+		}
+	}
+
+	out
+}
+
+fn write_rle_channel(out: &mut Vec<u8>, data: &[u8]) {
+	let mut i = 0;
+	while i < data.len() {
+		// find the next run of at least 4 equal bytes
+		let mut run_start = i;
+		let mut run_len = 0;
+		while run_start < data.len() {
+			run_len = 1;
+			while run_start + run_len < data.len()
+				&& run_len < MAX_RUN
+				&& data[run_start + run_len] == data[run_start]
+			{
+				run_len += 1;
+			}
+			if run_len >= 4 {
+				break;
+			}
+			run_start += run_len;
+		}
+
+		// literal bytes up to the run
+		while i < run_start {
+			let count = (run_start - i).min(MAX_RUN + 1);
+			out.push(count as u8);
+			out.extend_from_slice(&data[i..i + count]);
+			i += count;
+		}
+
+		if run_len >= 4 && run_start < data.len() {
+			out.push(128 + run_len as u8);
+			out.push(data[run_start]);
+			i = run_start + run_len;
+		}
+	}
+}
+
+/// Decodes Radiance data with the standard `-Y height +X width` orientation.
+pub fn decode_hdr(bytes: &[u8]) -> Result<HdrImage> {
+	let mut pos = 0;
+	let read_line = |pos: &mut usize| -> Result<String> {
+		let start = *pos;
+		while *pos < bytes.len() && bytes[*pos] != b'\n' {
+			*pos += 1;
+		}
+		if *pos >= bytes.len() {
+			return Err(invalid("unexpected end of header"));
+		}
+		let line = String::from_utf8_lossy(&bytes[start..*pos]).into_owned();
+		*pos += 1;
+		Ok(line)
+	};
+
+	let magic = read_line(&mut pos)?;
+	if !magic.starts_with("#?") {
+		return Err(invalid("missing #? signature"));
+	}
+
+	loop {
+		let line = read_line(&mut pos)?;
+		if line.is_empty() {
+			break;
+		}
+		if let Some(format) = line.strip_prefix("FORMAT=")
+			&& format.trim() != "32-bit_rle_rgbe"
+		{
+			return Err(invalid("only 32-bit_rle_rgbe is supported"));
+		}
+	}
+
+	let resolution = read_line(&mut pos)?;
+	let parts: Vec<&str> = resolution.split_whitespace().collect();
+	if parts.len() != 4 || parts[0] != "-Y" || parts[2] != "+X" {
+		return Err(invalid("only -Y height +X width orientation is supported"));
+	}
+	let height: usize = parts[1].parse().map_err(|_| invalid("bad height"))?;
+	let width: usize = parts[3].parse().map_err(|_| invalid("bad width"))?;
+	if width == 0 || height == 0 {
+		return Err(invalid("empty image"));
+	}
+
+	// don't trust the header with the allocation, the data has to cover every scanline
+	let remaining = bytes.len() - pos;
+	if height
+		.checked_mul(min_scanline_bytes(width))
+		.is_none_or(|n| n > remaining)
+	{
+		return Err(invalid("size exceeds pixel data"));
+	}
+
+	let mut data = vec![0.0; width * height * 4];
+	let mut scanline = vec![[0u8; 4]; width];
+
+	for y in 0..height {
+		read_scanline(bytes, &mut pos, &mut scanline)?;
+		for (x, px) in scanline.iter().enumerate() {
+			let rgb = rgbe_to_rgb(*px);
+			let i = (y * width + x) * 4;
+			data[i] = rgb[0];
+			data[i + 1] = rgb[1];
+			data[i + 2] = rgb[2];
+			data[i + 3] = 1.0;
+		}
+	}
+
+	Ok(HdrImage {
+		width: width as u32,
+		height: height as u32,
+		data,
+	})
+}
+
+// Smallest possible encoding of a scanline, a run per 127 pixels in each channel.
+fn min_scanline_bytes(w: usize) -> usize {
+	if (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&w) {
+		4 + 4 * 2 * w.div_ceil(MAX_RUN)
+	} else {
+		w * 4
+	}
+}
+
+fn read_scanline(bytes: &[u8], pos: &mut usize, scanline: &mut [[u8; 4]]) -> Result<()> {
+	let w = scanline.len();
+	let take = |pos: &mut usize, n: usize| -> Result<&[u8]> {
+		if *pos + n > bytes.len() {
+			return Err(invalid("unexpected end of pixel data"));
+		}
+		let s = &bytes[*pos..*pos + n];
+		*pos += n;
+		Ok(s)
+	};
+
+	let is_rle = (MIN_RLE_WIDTH..=MAX_RLE_WIDTH).contains(&w)
+		&& *pos + 4 <= bytes.len()
+		&& bytes[*pos] == 2
+		&& bytes[*pos + 1] == 2
+		&& bytes[*pos + 2] & 0x80 == 0;
+
+	if !is_rle {
+		for px in scanline.iter_mut() {
+			px.copy_from_slice(take(pos, 4)?);
+		}
+		return Ok(());
+	}
+
+	let header = take(pos, 4)?;
+	if ((header[2] as usize) << 8 | header[3] as usize) != w {
+		return Err(invalid("scanline width mismatch"));
+	}
+
+	for c in 0..4 {
+		let mut x = 0;
+		while x < w {
+			let count = take(pos, 1)?[0] as usize;
+			if count > 128 {
+				let run = count - 128;
+				if x + run > w {
+					return Err(invalid("run exceeds scanline"));
+				}
+				let value = take(pos, 1)?[0];
+				for px in &mut scanline[x..x + run] {
+					px[c] = value;
+				}
+				x += run;
+			} else {
+				if count == 0 || x + count > w {
+					return Err(invalid("bad literal count"));
+				}
+				let values = take(pos, count)?;
+				for (px, v) in scanline[x..x + count].iter_mut().zip(values) {
+					px[c] = *v;
+				}
+				x += count;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+pub fn read_hdr(path: impl AsRef<Path>) -> Result<HdrImage> {
+	decode_hdr(&fs::read(path)?)
+}
+
+pub fn write_hdr(path: impl AsRef<Path>, width: u32, height: u32, rgba: &[f32]) -> Result<()> {
+	fs::write(path, encode_hdr(width, height, rgba))
+}
+
+pub fn hdr_texture(p: &mut Painter, img: &HdrImage) -> Layer {
+	p.layer()
+		.with_size(img.width, img.height)
+		.with_format(wgpu::TextureFormat::Rgba32Float)
+		.with_static_texture_data(bytemuck::cast_slice(&img.data))
+		.create()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn test_pixels(width: u32, height: u32) -> Vec<f32> {
+		let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+		for i in 0..width * height {
+			let t = i as f32;
+			// mix of flat regions (RLE runs) and varying HDR values
+			let flat = (i / 13) % 2 == 0;
+			rgba.push(if flat {
+				2.5
+			} else {
+				(t * 0.37).sin().abs() * 40.0
+			});
+			rgba.push(if flat { 0.25 } else { (t * 0.11).cos().abs() });
+			rgba.push(if flat { 0.0 } else { t * 0.001 });
+			rgba.push(1.0);
+		}
+		rgba
+	}
+
+	fn assert_close(a: &[f32], b: &[f32]) {
+		assert_eq!(a.len(), b.len());
+		for (pa, pb) in a.chunks(4).zip(b.chunks(4)) {
+			let max = pa[0].max(pa[1]).max(pa[2]);
+			for c in 0..3 {
+				// rgbe keeps 8 bits of mantissa relative to the brightest channel
+				assert!(
+					(pa[c] - pb[c]).abs() <= max / 128.0 + 1e-6,
+					"{pa:?} != {pb:?}"
+				);
+			}
+			assert_eq!(pb[3], 1.0);
+		}
+	}
+
+	#[test]
+	fn round_trip_rle() {
+		let (w, h) = (64, 5);
+		let rgba = test_pixels(w, h);
+		let bytes = encode_hdr(w, h, &rgba);
+		let img = decode_hdr(&bytes).unwrap();
+		assert_eq!((img.width, img.height), (w, h));
+		assert_close(&rgba, &img.data);
+		// flat regions must actually be compressed
+		assert!(bytes.len() < (w * h * 4) as usize);
+	}
+
+	#[test]
+	fn round_trip_flat() {
+		let (w, h) = (5, 3);
+		let rgba = test_pixels(w, h);
+		let img = decode_hdr(&encode_hdr(w, h, &rgba)).unwrap();
+		assert_close(&rgba, &img.data);
+	}
+
+	#[test]
+	fn rgbe_zero_and_exact() {
+		assert_eq!(rgb_to_rgbe(0.0, 0.0, 0.0), [0, 0, 0, 0]);
+		assert_eq!(rgbe_to_rgb([0, 0, 0, 0]), [0.0; 3]);
+		let rgb = rgbe_to_rgb(rgb_to_rgbe(1.0, 0.5, 0.25));
+		assert!((rgb[0] - 1.0).abs() < 0.01);
+		assert!((rgb[1] - 0.5).abs() < 0.01);
+		assert!((rgb[2] - 0.25).abs() < 0.01);
+	}
+
+	#[test]
+	fn rgbe_clamps_out_of_range() {
+		let max = [255, 0, 0, 255];
+		assert_eq!(rgb_to_rgbe(f32::INFINITY, 0.0, 0.0), max);
+		assert_eq!(rgb_to_rgbe(f32::MAX, 0.0, 0.0), max);
+		assert_eq!(rgb_to_rgbe(f32::INFINITY, f32::NAN, -1.0), max);
+		assert_eq!(rgb_to_rgbe(f32::NAN, f32::NEG_INFINITY, 0.0), [0, 0, 0, 0]);
+		assert_eq!(rgb_to_rgbe(MAX_RGBE, MAX_RGBE / 2.0, 0.0)[3], 255);
+		assert!(rgbe_to_rgb(max)[0].is_finite());
+	}
+
+	#[test]
+	fn rejects_garbage() {
+		assert!(decode_hdr(b"not an hdr file\n").is_err());
+		let mut bytes = encode_hdr(16, 2, &test_pixels(16, 2));
+		bytes.truncate(bytes.len() - 10);
+		assert!(decode_hdr(&bytes).is_err());
+
+		// a huge size in the header must fail before allocating
+		let header = b"#?RADIANCE\n\n-Y 1000000 +X 1000000\n\x02\x02";
+		assert!(decode_hdr(header).is_err());
+		let overflow = format!("#?RADIANCE\n\n-Y {} +X 4\n", usize::MAX);
+		assert!(decode_hdr(overflow.as_bytes()).is_err());
+	}
+
+	#[test]
+	fn rejects_empty_size() {
+		for resolution in ["-Y 0 +X 4", "-Y 4 +X 0", "-Y 0 +X 0"] {
+			let header = format!("#?RADIANCE\n\n{resolution}\n");
+			let Err(err) = decode_hdr(header.as_bytes()) else {
+				panic!("{resolution} must be rejected");
+			};
+			assert!(
+				err.to_string().contains("empty image"),
+				"{resolution}: {err}"
+			);
+		}
+	}
+}
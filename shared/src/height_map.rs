@@ -0,0 +1,284 @@
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+// Height samples wrap around all edges, so results of tileable inputs stay seamless.
+struct HeightField<'a> {
+	width: usize,
+	height: usize,
+	data: &'a [f32],
+}
+
+impl HeightField<'_> {
+	fn at(&self, x: isize, y: isize) -> f32 {
+		let x = x.rem_euclid(self.width as isize) as usize;
+		let y = y.rem_euclid(self.height as isize) as usize;
+		self.data[y * self.width + x]
+	}
+
+	// Sobel gradient in height units per pixel.
+	fn gradient(&self, x: isize, y: isize) -> (f32, f32) {
+		let h = |dx, dy| self.at(x + dx, y + dy);
+		let dx = (h(1, -1) + 2.0 * h(1, 0) + h(1, 1) - h(-1, -1) - 2.0 * h(-1, 0) - h(-1, 1)) / 8.0;
+		let dy = (h(-1, 1) + 2.0 * h(0, 1) + h(1, 1) - h(-1, -1) - 2.0 * h(0, -1) - h(1, -1)) / 8.0;
+		(dx, dy)
+	}
+
+	fn laplacian(&self, x: isize, y: isize) -> f32 {
+		self.at(x + 1, y) + self.at(x - 1, y) + self.at(x, y + 1) + self.at(x, y - 1)
+			- 4.0 * self.at(x, y)
+	}
+}
+
+fn for_each_pixel(
+	width: u32,
+	height: u32,
+	heights: &[f32],
+	f: impl Fn(&HeightField, isize, isize) -> [f32; 4],
+) -> Vec<f32> {
+	assert_eq!(heights.len(), (width * height) as usize);
+	let field = HeightField {
+		width: width as usize,
+		height: height as usize,
+		data: heights,
+	};
+	let mut rgba = Vec::with_capacity(heights.len() * 4);
+	for y in 0..height as isize {
+		for x in 0..width as isize {
+			rgba.extend_from_slice(&f(&field, x, y));
+		}
+	}
+	rgba
+}
+
+/// Channel value that converts to a height in [0, 1].
+pub trait HeightSample: Copy {
+	fn to_height(self) -> f32;
+}
+
+impl HeightSample for f32 {
+	fn to_height(self) -> f32 {
+		self
+	}
+}
+
+impl HeightSample for u8 {
+	fn to_height(self) -> f32 {
+		self as f32 / 255.0
+	}
+}
+
+/// Copies one channel of an interleaved RGBA buffer, e.g. `tiled_noise_rgba` output.
+/// u8 values are normalized, so both buffer types give heights in the same range.
+pub fn extract_channel<T: HeightSample>(rgba: &[T], channel: usize) -> Vec<f32> {
+	rgba
+		.iter()
+		.skip(channel)
+		.step_by(4)
+		.map(|v| v.to_height())
+		.collect()
+}
+
+/// Tangent space normal map, encoded as `n * 0.5 + 0.5` in rgb with the height in alpha.
+/// `strength` scales the height differences, larger values give steeper normals.
+/// Green follows the OpenGL convention, +y points towards row 0 of the buffer.
+pub fn normal_map(width: u32, height: u32, heights: &[f32], strength: f32) -> Vec<f32> {
+	for_each_pixel(width, height, heights, |f, x, y| {
+		let (dx, dy) = f.gradient(x, y);
+		let nx = -dx * strength;
+		let ny = dy * strength;
+		let len = (nx * nx + ny * ny + 1.0).sqrt();
+		[
+			nx / len * 0.5 + 0.5,
+			ny / len * 0.5 + 0.5,
+			1.0 / len * 0.5 + 0.5,
+			f.at(x, y),
+		]
+	})
+}
+
+/// Slope in red (0 flat, 1 vertical), curvature in green (0.5 flat, above convex,
+/// below concave), height in blue.
+pub fn slope_curvature_map(width: u32, height: u32, heights: &[f32], strength: f32) -> Vec<f32> {
+	for_each_pixel(width, height, heights, |f, x, y| {
+		let (dx, dy) = f.gradient(x, y);
+		let slope = ((dx * dx + dy * dy).sqrt() * strength).atan() / std::f32::consts::FRAC_PI_2;
+		let curvature = (-f.laplacian(x, y) * strength).clamp(-1.0, 1.0) * 0.5 + 0.5;
+		[slope, curvature, f.at(x, y), 1.0]
+	})
+}
+
+/// Horizon based ambient occlusion. Marches `directions` rays up to `radius` pixels and
+/// averages how much of the sky is hidden. `strength` converts height units to pixels.
+/// Returns 1.0 for fully open and lower values for occluded pixels, in all channels.
+pub fn ambient_occlusion_map(
+	width: u32,
+	height: u32,
+	heights: &[f32],
+	strength: f32,
+	radius: u32,
+	directions: u32,
+) -> Vec<f32> {
+	let dirs: Vec<(f32, f32)> = (0..directions)
+		.map(|i| {
+			let a = std::f32::consts::TAU * i as f32 / directions as f32;
+			(a.cos(), a.sin())
+		})
+		.collect();
+
+	for_each_pixel(width, height, heights, |f, x, y| {
+		let h0 = f.at(x, y) * strength;
+		let mut occlusion = 0.0;
+		for &(cx, cy) in &dirs {
+			let mut max_tan: f32 = 0.0;
+			for step in 1..=radius {
+				let d = step as f32;
+				let sx = x + (cx * d).round() as isize;
+				let sy = y + (cy * d).round() as isize;
+				max_tan = max_tan.max((f.at(sx, sy) * strength - h0) / d);
+			}
+			// sine of the horizon angle
+			occlusion += max_tan / (1.0 + max_tan * max_tan).sqrt();
+		}
+		let ao = 1.0 - occlusion / directions as f32;
+		[ao, ao, ao, 1.0]
+	})
+}
+
+pub fn rgba_f32_to_u8(rgba: &[f32]) -> Vec<u8> {
+	rgba
+		.iter()
+		.map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+		.collect()
+}
+
+// Derived data is not color, so it is stored without sRGB encoding.
+fn data_texture(p: &mut Painter, width: u32, height: u32, rgba: &[f32]) -> Layer {
+	p.layer()
+		.with_size(width, height)
+		.with_format(wgpu::TextureFormat::Rgba8Unorm)
+		.with_static_texture_data(&rgba_f32_to_u8(rgba))
+		.create()
+}
+
+pub fn normal_map_texture(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	heights: &[f32],
+	strength: f32,
+) -> Layer {
+	let rgba = normal_map(width, height, heights, strength);
+	data_texture(p, width, height, &rgba)
+}
+
+pub fn slope_curvature_texture(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	heights: &[f32],
+	strength: f32,
+) -> Layer {
+	let rgba = slope_curvature_map(width, height, heights, strength);
+	data_texture(p, width, height, &rgba)
+}
+
+pub fn ambient_occlusion_texture(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	heights: &[f32],
+	strength: f32,
+	radius: u32,
+) -> Layer {
+	let rgba = ambient_occlusion_map(width, height, heights, strength, radius, 8);
+	data_texture(p, width, height, &rgba)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn bumps(width: u32, height: u32) -> Vec<f32> {
+		let mut h = Vec::new();
+		for y in 0..height {
+			for x in 0..width {
+				let u = x as f32 / width as f32 * std::f32::consts::TAU;
+				let v = y as f32 / height as f32 * std::f32::consts::TAU;
+				h.push((u.sin() * (2.0 * v).cos()).abs());
+			}
+		}
+		h
+	}
+
+	#[test]
+	fn extracts_normalized_channel() {
+		let bytes = [0, 51, 0, 0, 255, 102, 0, 0];
+		assert_eq!(extract_channel(&bytes, 0), [0.0, 1.0]);
+		assert_eq!(extract_channel(&bytes, 1), [0.2, 0.4]);
+
+		let floats = [0.0, 0.2, 0.0, 0.0, 1.0, 0.4, 0.0, 0.0];
+		assert_eq!(extract_channel(&floats, 0), [0.0, 1.0]);
+		assert_eq!(extract_channel(&floats, 1), [0.2, 0.4]);
+	}
+
+	#[test]
+	fn flat_height_points_up() {
+		let flat = vec![0.3; 16];
+		let n = normal_map(4, 4, &flat, 10.0);
+		for px in n.chunks(4) {
+			assert_eq!(px, [0.5, 0.5, 1.0, 0.3]);
+		}
+		let ao = ambient_occlusion_map(4, 4, &flat, 10.0, 3, 8);
+		assert!(ao.iter().all(|v| *v == 1.0));
+		let sc = slope_curvature_map(4, 4, &flat, 1.0);
+		assert!(sc.chunks(4).all(|px| px[0] == 0.0 && px[1] == 0.5));
+	}
+
+	#[test]
+	fn ramp_normal_tilts_against_slope() {
+		// heights rising in +x
+		let ramp: Vec<f32> = (0..9).map(|i| (i % 3) as f32).collect();
+		let n = normal_map(3, 3, &ramp, 1.0);
+		// center pixel, neighbours 0 and 2
+		let px = &n[4 * 4..4 * 4 + 4];
+		assert!(px[0] < 0.5);
+		assert_eq!(px[1], 0.5);
+	}
+
+	#[test]
+	fn wraps_seamlessly() {
+		let (w, h) = (16, 8);
+		let heights = bumps(w, h);
+		// rolling the input by some columns must roll the output the same way
+		let shift = 5;
+		let rolled: Vec<f32> = (0..(w * h) as usize)
+			.map(|i| {
+				let x = i % w as usize;
+				let y = i / w as usize;
+				heights[y * w as usize + (x + shift) % w as usize]
+			})
+			.collect();
+
+		let a = ambient_occlusion_map(w, h, &heights, 4.0, 3, 8);
+		let b = ambient_occlusion_map(w, h, &rolled, 4.0, 3, 8);
+		let n_a = normal_map(w, h, &heights, 4.0);
+		let n_b = normal_map(w, h, &rolled, 4.0);
+		for i in 0..(w * h) as usize {
+			let x = i % w as usize;
+			let y = i / w as usize;
+			let j = y * w as usize + (x + shift) % w as usize;
+			for c in 0..4 {
+				assert!((a[j * 4 + c] - b[i * 4 + c]).abs() < 1e-6);
+				assert!((n_a[j * 4 + c] - n_b[i * 4 + c]).abs() < 1e-6);
+			}
+		}
+	}
+
+	#[test]
+	fn pits_are_occluded() {
+		let mut heights = vec![1.0; 25];
+		heights[12] = 0.0;
+		let ao = ambient_occlusion_map(5, 5, &heights, 1.0, 2, 8);
+		assert!(ao[12 * 4] < 0.5);
+		assert_eq!(ao[0], 1.0);
+	}
+}
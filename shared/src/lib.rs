@@ -1,13 +1,25 @@
+use effect_layer::StaticEffectLayerBuilder;
 use noise::{NoiseFn, Simplex};
+use texture_format::{TexelFormat, texture_from_rgba_f32};
 use trivalibs::{
-	painter::{
-		Painter, binding::ValueBinding, layer::Layer, prelude::BINDING_BUFFER_FRAG, shade::Shade, wgpu,
-	},
+	painter::{Painter, binding::ValueBinding, layer::Layer, shade::Shade, wgpu},
 	prelude::*,
 	rendering::texture::f64_to_u8,
 };
 
+pub mod atlas;
+pub mod blue_noise;
+pub mod cpu_render;
+pub mod distance_field;
+pub mod effect_layer;
+pub mod golden;
+pub mod gradient;
+pub mod hdr;
+pub mod height_map;
+pub mod lut;
+pub mod mipmap;
 mod plate_geometry;
+pub mod texture_format;
 
 pub fn rand_rgba_f32(width: u32, height: u32) -> Vec<f32> {
 	let mut rgba = vec![0.0; (width * height * 4) as usize];
@@ -122,6 +134,28 @@ pub fn noise_texture_f32(p: &mut Painter, width: u32, height: u32, noise_scale:
 		.create()
 }
 
+/// Tiled noise converted to `format`, e.g. `TexelFormat::Rgba16Float` for half float
+/// layers. Single channel formats keep the first noise octave.
+pub fn noise_texture_with_format(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	noise_scale: f64,
+	format: TexelFormat,
+) -> Layer {
+	let rgba = tiled_noise_rgba(width, height, noise_scale, 2.0, |x| x as f32);
+	texture_from_rgba_f32(p, width, height, &rgba, format)
+}
+
+pub fn noise_texture_f16(p: &mut Painter, width: u32, height: u32, noise_scale: f64) -> Layer {
+	noise_texture_with_format(p, width, height, noise_scale, TexelFormat::Rgba16Float)
+}
+
+/// Effect layer with the size bound as `UVec2` at binding 0 and every given binding as
+/// fragment buffer. Use `StaticEffectLayerBuilder` for samplers, layers or other layouts.
+///
+/// Panics if the binding indices are not contiguous from 1 or one of them is used twice,
+/// use `StaticEffectLayerBuilder::create` to get the error instead.
 pub fn static_effect_layer(
 	p: &mut Painter,
 	width: u32,
@@ -129,30 +163,17 @@ pub fn static_effect_layer(
 	format: wgpu::TextureFormat,
 	bindings: Vec<(u32, ValueBinding)>,
 ) -> (Layer, Shade) {
-	let bindings_len = bindings.len() + 1;
-	let mut shade_bindings = Vec::with_capacity(bindings.len() + 1);
-	for _ in 0..bindings_len {
-		shade_bindings.push(BINDING_BUFFER_FRAG);
+	let mut builder = StaticEffectLayerBuilder::new(p, width, height)
+		.with_format(format)
+		.with_size_binding(0);
+	for (index, binding) in bindings {
+		builder = builder.with_buffer(index, binding);
 	}
 
-	let shade = p.shade_effect().with_bindings(shade_bindings).create();
-
-	let size = p.bind_const_uvec2(uvec2(width, height));
-
-	let mut new_bindings = Vec::with_capacity(bindings_len);
-	new_bindings.push((0, size));
-	for b in bindings.into_iter() {
-		new_bindings.push(b);
+	match builder.create() {
+		Ok(res) => res,
+		Err(e) => panic!("static_effect_layer: {e} (binding 0 is reserved for the size)"),
 	}
-
-	let layer = p
-		.single_effect_layer(shade)
-		.with_size(width, height)
-		.with_bindings(new_bindings)
-		.with_format(format)
-		.create();
-
-	(layer, shade)
 }
 
 pub fn static_effect_layer_u8(
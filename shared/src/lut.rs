@@ -0,0 +1,367 @@
+//! Parsing and upload of `.cube` 3D color lookup tables.
+//!
+//! The table is uploaded as a 2D strip instead of a `TextureDimension::D3` texture. The
+//! strip is `size * size` texels wide and `size` high. It holds one `size` x `size` slice
+//! per blue value, side by side from left to right. Within a slice red runs along x and
+//! green along y.
+//!
+//! Painter layers are always 2D and bind as `texture_2d`, so a 3D texture would need its
+//! own texture, view and bind group layout outside of the painter. With the strip, a LUT
+//! is a regular layer with a regular linear sampler. The sampler interpolates red and
+//! green, and `shared_nostd::lut::apply_lut` blends two neighbouring blue slices. The
+//! result is the same trilinear lookup for two texture reads instead of one.
+//! `CubeLut::sample_strip` does the same lookup on the CPU.
+
+use crate::texture_format::{TexelFormat, texture_from_rgba_f32};
+use std::{
+	io::{Error, ErrorKind, Result},
+	path::Path,
+};
+use trivalibs::{
+	painter::{Painter, layer::Layer},
+	prelude::*,
+};
+
+pub const MAX_LUT_SIZE: u32 = 65;
+
+/// 3D color lookup table as stored in `.cube` files (Adobe / Resolve format).
+/// Entries are ordered with red changing fastest, then green, then blue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeLut {
+	pub title: Option<String>,
+	pub size: u32,
+	pub domain_min: Vec3,
+	pub domain_max: Vec3,
+	pub data: Vec<Vec3>,
+}
+
+fn invalid(line: usize, msg: impl std::fmt::Display) -> Error {
+	Error::new(ErrorKind::InvalidData, format!("cube line {line}: {msg}"))
+}
+
+fn parse_vec3<'a>(line: usize, mut values: impl Iterator<Item = &'a str>) -> Result<Vec3> {
+	let mut v = [0.0; 3];
+	for c in v.iter_mut() {
+		let s = values
+			.next()
+			.ok_or_else(|| invalid(line, "expected three values"))?;
+		*c = s
+			.parse::<f32>()
+			.map_err(|_| invalid(line, format!("invalid number '{s}'")))?;
+		if !c.is_finite() {
+			return Err(invalid(line, "values must be finite"));
+		}
+	}
+	if values.next().is_some() {
+		return Err(invalid(line, "expected three values"));
+	}
+	Ok(Vec3::from(v))
+}
+
+pub fn parse_cube(src: &str) -> Result<CubeLut> {
+	let mut title = None;
+	let mut size = None;
+	let mut domain_min = Vec3::ZERO;
+	let mut domain_max = Vec3::ONE;
+	let mut data = Vec::new();
+
+	for (i, line) in src.lines().enumerate() {
+		let n = i + 1;
+		// comments only start at the beginning of a line, titles may contain '#'
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+
+		let mut parts = line.split_whitespace();
+		let keyword = parts.next().unwrap();
+
+		if !data.is_empty() && keyword.starts_with(|c: char| c.is_ascii_alphabetic()) {
+			return Err(invalid(n, "keywords must precede the table data"));
+		}
+
+		match keyword {
+			"TITLE" => {
+				let rest = line["TITLE".len()..].trim();
+				title = Some(rest.trim_matches('"').to_string());
+			}
+			"LUT_3D_SIZE" => {
+				let s: u32 = parts
+					.next()
+					.and_then(|s| s.parse().ok())
+					.ok_or_else(|| invalid(n, "invalid LUT_3D_SIZE"))?;
+				if !(2..=MAX_LUT_SIZE).contains(&s) {
+					return Err(invalid(
+						n,
+						format!("LUT_3D_SIZE must be between 2 and {MAX_LUT_SIZE}"),
+					));
+				}
+				size = Some(s);
+			}
+			"LUT_1D_SIZE" => return Err(invalid(n, "1D LUTs are not supported")),
+			"DOMAIN_MIN" => domain_min = parse_vec3(n, parts)?,
+			"DOMAIN_MAX" => domain_max = parse_vec3(n, parts)?,
+			// Resolve variant of DOMAIN_MIN / DOMAIN_MAX
+			"LUT_3D_INPUT_RANGE" => {
+				let range = parts
+					.map(|s| s.parse::<f32>())
+					.collect::<std::result::Result<Vec<_>, _>>()
+					.map_err(|_| invalid(n, "invalid LUT_3D_INPUT_RANGE"))?;
+				let [min, max] = range[..] else {
+					return Err(invalid(n, "LUT_3D_INPUT_RANGE expects two values"));
+				};
+				domain_min = Vec3::splat(min);
+				domain_max = Vec3::splat(max);
+			}
+			k if k.starts_with(|c: char| c.is_ascii_alphabetic()) => {
+				return Err(invalid(n, format!("unknown keyword '{k}'")));
+			}
+			_ => {
+				if size.is_none() {
+					return Err(invalid(n, "LUT_3D_SIZE must precede the table data"));
+				}
+				data.push(parse_vec3(n, line.split_whitespace())?);
+			}
+		}
+	}
+
+	let size = size.ok_or_else(|| Error::new(ErrorKind::InvalidData, "missing LUT_3D_SIZE"))?;
+	let expected = (size * size * size) as usize;
+	if data.len() != expected {
+		return Err(Error::new(
+			ErrorKind::InvalidData,
+			format!("expected {expected} table entries, found {}", data.len()),
+		));
+	}
+	if domain_min.cmpge(domain_max).any() {
+		return Err(Error::new(
+			ErrorKind::InvalidData,
+			"DOMAIN_MIN must be smaller than DOMAIN_MAX",
+		));
+	}
+
+	Ok(CubeLut {
+		title,
+		size,
+		domain_min,
+		domain_max,
+		data,
+	})
+}
+
+pub fn read_cube(path: impl AsRef<Path>) -> Result<CubeLut> {
+	parse_cube(&std::fs::read_to_string(path)?)
+}
+
+impl CubeLut {
+	/// Lookup table that maps every color to itself.
+	pub fn identity(size: u32) -> Self {
+		let n = size as usize;
+		let max = (size - 1) as f32;
+		let data = (0..n * n * n)
+			.map(|i| vec3((i % n) as f32, (i / n % n) as f32, (i / (n * n)) as f32) / max)
+			.collect();
+		Self {
+			title: None,
+			size,
+			domain_min: Vec3::ZERO,
+			domain_max: Vec3::ONE,
+			data,
+		}
+	}
+
+	fn at(&self, r: u32, g: u32, b: u32) -> Vec3 {
+		self.data[((b * self.size + g) * self.size + r) as usize]
+	}
+
+	/// Trilinear lookup on the CPU, `color` is given in the input domain.
+	pub fn sample(&self, color: Vec3) -> Vec3 {
+		let max = (self.size - 1) as f32;
+		let c = ((color - self.domain_min) / (self.domain_max - self.domain_min))
+			.clamp(Vec3::ZERO, Vec3::ONE)
+			* max;
+		let i = c.floor().min(Vec3::splat(max - 1.0));
+		let f = c - i;
+		let (r, g, b) = (i.x as u32, i.y as u32, i.z as u32);
+
+		let lerp_rg = |b| {
+			let c0 = self.at(r, g, b).lerp(self.at(r + 1, g, b), f.x);
+			let c1 = self.at(r, g + 1, b).lerp(self.at(r + 1, g + 1, b), f.x);
+			c0.lerp(c1, f.y)
+		};
+		lerp_rg(b).lerp(lerp_rg(b + 1), f.z)
+	}
+
+	// Texel of the strip at column x and row y, see `rgba_strip_f32`.
+	fn strip_texel(&self, x: u32, y: u32) -> Vec4 {
+		self.at(x % self.size, y, x / self.size).extend(1.0)
+	}
+
+	/// Lookup through the strip on the CPU, the same way a shader samples `lut_texture`
+	/// with `shared_nostd::lut::apply_lut_domain` and a linear, edge clamping sampler.
+	/// `color` is given in the input domain. The result matches `sample` up to rounding.
+	pub fn sample_strip(&self, color: Vec3) -> Vec3 {
+		let (w, h) = (self.size * self.size, self.size);
+		let bilinear = |uv: Vec2| {
+			let p = uv * vec2(w as f32, h as f32) - 0.5;
+			let i = p.floor();
+			let f = p - i;
+			let x0 = (i.x.max(0.0) as u32).min(w - 1);
+			let y0 = (i.y.max(0.0) as u32).min(h - 1);
+			let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+			let top = self.strip_texel(x0, y0).lerp(self.strip_texel(x1, y0), f.x);
+			let bottom = self.strip_texel(x0, y1).lerp(self.strip_texel(x1, y1), f.x);
+			top.lerp(bottom, f.y)
+		};
+		shared_nostd::lut::apply_lut_domain(
+			color,
+			self.size,
+			self.domain_min,
+			self.domain_max,
+			bilinear,
+		)
+	}
+
+	/// RGBA data in the order of the table, red changing fastest. For textures use the
+	/// strip layout of `rgba_strip_f32`.
+	pub fn rgba_f32(&self) -> Vec<f32> {
+		self
+			.data
+			.iter()
+			.flat_map(|c| [c.x, c.y, c.z, 1.0])
+			.collect()
+	}
+
+	/// RGBA data of the table unwrapped into a `size * size` x `size` 2D strip, with one
+	/// blue slice after the other. This is the layout `shared_nostd::lut` samples from.
+	pub fn rgba_strip_f32(&self) -> Vec<f32> {
+		let n = self.size;
+		let mut rgba = Vec::with_capacity(self.data.len() * 4);
+		for g in 0..n {
+			for b in 0..n {
+				for r in 0..n {
+					let c = self.at(r, g, b);
+					rgba.extend_from_slice(&[c.x, c.y, c.z, 1.0]);
+				}
+			}
+		}
+		rgba
+	}
+}
+
+/// Uploads the LUT as 2D strip texture for `shared_nostd::lut::apply_lut`. The strip is
+/// stored as linear half floats, so values outside of [0, 1] from HDR or wide gamut LUTs
+/// are kept. Needs a linear filtering sampler.
+pub fn lut_texture(p: &mut Painter, lut: &CubeLut) -> Layer {
+	texture_from_rgba_f32(
+		p,
+		lut.size * lut.size,
+		lut.size,
+		&lut.rgba_strip_f32(),
+		TexelFormat::Rgba16Float,
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const INVERT: &str = "# inverts all colors
+TITLE \"invert\"
+LUT_3D_SIZE 2
+DOMAIN_MIN 0 0 0
+DOMAIN_MAX 1 1 1
+
+1 1 1
+# red
+0 1 1
+1 0 1
+0 0 1
+1 1 0
+0 1 0
+1 0 0
+0 0 0
+";
+
+	fn assert_close(a: Vec3, b: Vec3) {
+		assert!((a - b).abs().max_element() < 1e-5, "{a} != {b}");
+	}
+
+	#[test]
+	fn parses_cube() {
+		let lut = parse_cube(INVERT).unwrap();
+		assert_eq!(lut.title.as_deref(), Some("invert"));
+		assert_eq!(lut.size, 2);
+		assert_eq!(lut.data.len(), 8);
+		assert_eq!(lut.data[1], vec3(0.0, 1.0, 1.0));
+		assert_eq!(lut.rgba_f32()[4..8], [0.0, 1.0, 1.0, 1.0]);
+		assert_close(lut.sample(vec3(0.25, 0.5, 1.0)), vec3(0.75, 0.5, 0.0));
+
+		let hash_title = INVERT.replace("\"invert\"", "\"look #2\"");
+		assert_eq!(
+			parse_cube(&hash_title).unwrap().title.as_deref(),
+			Some("look #2")
+		);
+
+		let wide = INVERT.replace("DOMAIN_MAX 1 1 1", "DOMAIN_MAX 2 2 2");
+		let lut = parse_cube(&wide).unwrap();
+		assert_close(lut.sample(vec3(1.0, 1.0, 1.0)), vec3(0.5, 0.5, 0.5));
+	}
+
+	#[test]
+	fn rejects_invalid_files() {
+		let missing_entry = INVERT.trim_end().rsplit_once('\n').unwrap().0;
+		assert!(parse_cube(missing_entry).is_err());
+		assert!(parse_cube(&INVERT.replace("LUT_3D_SIZE 2", "LUT_3D_SIZE 66")).is_err());
+		assert!(parse_cube(&INVERT.replace("LUT_3D_SIZE 2", "LUT_1D_SIZE 2")).is_err());
+		assert!(parse_cube(&INVERT.replace("DOMAIN_MAX 1 1 1", "DOMAIN_MAX 0 1 1")).is_err());
+		assert!(parse_cube(&INVERT.replace("1 0 0\n", "1 0\n")).is_err());
+		assert!(parse_cube(&INVERT.replace("1 0 0\n", "1 x 0\n")).is_err());
+		assert!(parse_cube(&format!("{INVERT}LUT_3D_SIZE 2\n")).is_err());
+	}
+
+	#[test]
+	fn largest_identity_round_trips() {
+		let lut = CubeLut::identity(MAX_LUT_SIZE);
+		let src = format!(
+			"LUT_3D_SIZE {MAX_LUT_SIZE}\n{}",
+			lut
+				.data
+				.iter()
+				.map(|c| format!("{} {} {}\n", c.x, c.y, c.z))
+				.collect::<String>()
+		);
+		assert_eq!(parse_cube(&src).unwrap(), lut);
+		let c = vec3(0.1, 0.7, 0.33);
+		assert_close(lut.sample(c), c);
+	}
+
+	#[test]
+	fn strip_sampling_matches_trilinear() {
+		let mut lut = CubeLut::identity(5);
+		for c in lut.data.iter_mut() {
+			*c = vec3(c.x * c.y, c.z * c.z, 1.0 - c.x);
+		}
+		let n = lut.size;
+		let strip = lut.rgba_strip_f32();
+		for y in 0..n {
+			for x in 0..n * n {
+				let i = ((y * n * n + x) * 4) as usize;
+				assert_eq!(lut.strip_texel(x, y), Vec4::from_slice(&strip[i..i + 4]));
+			}
+		}
+
+		for c in [
+			vec3(0.0, 0.0, 0.0),
+			vec3(1.0, 1.0, 1.0),
+			vec3(0.3, 0.6, 0.9),
+			vec3(0.55, 0.1, 0.5),
+		] {
+			assert_close(lut.sample_strip(c), lut.sample(c));
+		}
+
+		lut.domain_max = Vec3::splat(2.0);
+		let c = vec3(0.6, 1.2, 1.8);
+		assert_close(lut.sample_strip(c), lut.sample(c));
+	}
+}
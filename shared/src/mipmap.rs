@@ -0,0 +1,357 @@
+use shared_nostd::color::{linear_to_srgb, srgb_to_linear};
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+const KAISER_ALPHA: f32 = 4.0;
+// filter radius in destination texels
+const KAISER_WIDTH: f32 = 1.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MipFilter {
+	/// Area average of the covered source texels.
+	Box,
+	/// Kaiser windowed sinc, sharper than box with less aliasing.
+	Kaiser,
+}
+
+pub struct MipLevel<T> {
+	pub width: u32,
+	pub height: u32,
+	pub data: Vec<T>,
+}
+
+pub fn mip_count(width: u32, height: u32) -> u32 {
+	32 - width.max(height).max(1).leading_zeros()
+}
+
+fn bessel_i0(x: f32) -> f32 {
+	let mut sum = 1.0;
+	let mut term = 1.0;
+	let half_x = x * 0.5;
+	for k in 1..20 {
+		term *= half_x / k as f32;
+		sum += term * term;
+	}
+	sum
+}
+
+fn sinc(x: f32) -> f32 {
+	if x.abs() < 1e-5 {
+		1.0
+	} else {
+		let px = std::f32::consts::PI * x;
+		px.sin() / px
+	}
+}
+
+fn kaiser(x: f32) -> f32 {
+	let t = x / KAISER_WIDTH;
+	if t.abs() >= 1.0 {
+		return 0.0;
+	}
+	bessel_i0(KAISER_ALPHA * (1.0 - t * t).sqrt()) / bessel_i0(KAISER_ALPHA)
+}
+
+// Per destination texel list of (source index, weight), weights sum to 1.
+fn filter_taps(src_len: u32, dst_len: u32, filter: MipFilter) -> Vec<Vec<(usize, f32)>> {
+	let scale = src_len as f32 / dst_len as f32;
+	let last = src_len as i32 - 1;
+
+	(0..dst_len)
+		.map(|x| {
+			let center = (x as f32 + 0.5) * scale;
+			let mut taps: Vec<(usize, f32)> = Vec::new();
+			let mut add = |i: i32, w: f32| {
+				let i = i.clamp(0, last) as usize;
+				match taps.iter_mut().find(|t| t.0 == i) {
+					Some(t) => t.1 += w,
+					None => taps.push((i, w)),
+				}
+			};
+
+			match filter {
+				MipFilter::Box => {
+					let start = center - scale * 0.5;
+					let end = center + scale * 0.5;
+					for i in start.floor() as i32..end.ceil() as i32 {
+						let coverage = (end.min(i as f32 + 1.0) - start.max(i as f32)).max(0.0);
+						if coverage > 0.0 {
+							add(i, coverage);
+						}
+					}
+				}
+				MipFilter::Kaiser => {
+					let radius = KAISER_WIDTH * scale;
+					for i in (center - radius).floor() as i32..(center + radius).ceil() as i32 {
+						let d = (i as f32 + 0.5 - center) / scale;
+						let w = sinc(d) * kaiser(d);
+						if w != 0.0 {
+							add(i, w);
+						}
+					}
+				}
+			}
+
+			let sum: f32 = taps.iter().map(|t| t.1).sum();
+			for t in taps.iter_mut() {
+				t.1 /= sum;
+			}
+			taps
+		})
+		.collect()
+}
+
+/// Downsamples an RGBA f32 image to half size (at least 1x1), clamping at the edges.
+pub fn downsample_rgba_f32(
+	width: u32,
+	height: u32,
+	rgba: &[f32],
+	filter: MipFilter,
+) -> MipLevel<f32> {
+	let dst_w = (width / 2).max(1);
+	let dst_h = (height / 2).max(1);
+	let taps_x = filter_taps(width, dst_w, filter);
+	let taps_y = filter_taps(height, dst_h, filter);
+
+	// horizontal pass
+	let mut tmp = vec![0.0; (dst_w * height * 4) as usize];
+	for y in 0..height as usize {
+		for (x, taps) in taps_x.iter().enumerate() {
+			let o = (y * dst_w as usize + x) * 4;
+			for &(sx, w) in taps {
+				let i = (y * width as usize + sx) * 4;
+				for c in 0..4 {
+					tmp[o + c] += rgba[i + c] * w;
+				}
+			}
+		}
+	}
+
+	// vertical pass
+	let mut data = vec![0.0; (dst_w * dst_h * 4) as usize];
+	for (y, taps) in taps_y.iter().enumerate() {
+		for x in 0..dst_w as usize {
+			let o = (y * dst_w as usize + x) * 4;
+			for &(sy, w) in taps {
+				let i = (sy * dst_w as usize + x) * 4;
+				for c in 0..4 {
+					data[o + c] += tmp[i + c] * w;
+				}
+			}
+		}
+	}
+
+	if filter == MipFilter::Kaiser {
+		// negative lobes can undershoot
+		for v in data.iter_mut() {
+			*v = v.max(0.0);
+		}
+	}
+
+	MipLevel {
+		width: dst_w,
+		height: dst_h,
+		data,
+	}
+}
+
+/// Full mip chain of an RGBA f32 image, level 0 is a copy of the input.
+pub fn mip_chain_rgba_f32(
+	width: u32,
+	height: u32,
+	rgba: &[f32],
+	filter: MipFilter,
+) -> Vec<MipLevel<f32>> {
+	let mut levels = vec![MipLevel {
+		width,
+		height,
+		data: rgba.to_vec(),
+	}];
+	for _ in 1..mip_count(width, height) {
+		let prev = levels.last().unwrap();
+		let next = downsample_rgba_f32(prev.width, prev.height, &prev.data, filter);
+		levels.push(next);
+	}
+	levels
+}
+
+/// Mip chain of RGBA u8 data. With `srgb` the color channels are averaged in linear
+/// space, which keeps bright/dark detail from darkening at lower levels. Alpha is
+/// always filtered linearly.
+pub fn mip_chain_rgba_u8(
+	width: u32,
+	height: u32,
+	rgba: &[u8],
+	filter: MipFilter,
+	srgb: bool,
+) -> Vec<MipLevel<u8>> {
+	let decode = |i: usize, v: u8| {
+		let v = v as f32 / 255.0;
+		if srgb && i % 4 != 3 {
+			srgb_to_linear(v)
+		} else {
+			v
+		}
+	};
+	let encode = |i: usize, v: f32| {
+		let v = v.clamp(0.0, 1.0);
+		let v = if srgb && i % 4 != 3 {
+			linear_to_srgb(v)
+		} else {
+			v
+		};
+		(v * 255.0).round() as u8
+	};
+
+	let linear: Vec<f32> = rgba
+		.iter()
+		.enumerate()
+		.map(|(i, v)| decode(i, *v))
+		.collect();
+
+	mip_chain_rgba_f32(width, height, &linear, filter)
+		.into_iter()
+		.map(|level| MipLevel {
+			width: level.width,
+			height: level.height,
+			data: level
+				.data
+				.iter()
+				.enumerate()
+				.map(|(i, v)| encode(i, *v))
+				.collect(),
+		})
+		.collect()
+}
+
+fn write_mip_levels(p: &mut Painter, layer: Layer, levels: &[MipLevel<u8>], bytes_per_pixel: u32) {
+	let texture = layer.texture(p);
+	for (mip, level) in levels.iter().enumerate().skip(1) {
+		p.queue.write_texture(
+			wgpu::TexelCopyTextureInfo {
+				texture,
+				mip_level: mip as u32,
+				origin: wgpu::Origin3d::ZERO,
+				aspect: wgpu::TextureAspect::All,
+			},
+			&level.data,
+			wgpu::TexelCopyBufferLayout {
+				offset: 0,
+				bytes_per_row: Some(level.width * bytes_per_pixel),
+				rows_per_image: Some(level.height),
+			},
+			wgpu::Extent3d {
+				width: level.width,
+				height: level.height,
+				depth_or_array_layers: 1,
+			},
+		);
+	}
+}
+
+fn base_level<T>(levels: &[MipLevel<T>]) -> &MipLevel<T> {
+	assert!(
+		!levels.is_empty(),
+		"mip chain needs at least the base level"
+	);
+	&levels[0]
+}
+
+/// Creates a static texture layer and uploads every level of the chain into its mips.
+///
+/// Panics if `levels` is empty.
+pub fn mipmapped_texture_u8(p: &mut Painter, levels: &[MipLevel<u8>]) -> Layer {
+	let base = base_level(levels);
+	let layer = p
+		.layer()
+		.with_size(base.width, base.height)
+		.with_mips_max(levels.len() as u32)
+		.with_static_texture_data(&base.data)
+		.create();
+
+	write_mip_levels(p, layer, levels, 4);
+	layer
+}
+
+pub fn mipmapped_texture_f32(p: &mut Painter, levels: &[MipLevel<f32>]) -> Layer {
+	let byte_levels: Vec<MipLevel<u8>> = levels
+		.iter()
+		.map(|l| MipLevel {
+			width: l.width,
+			height: l.height,
+			data: bytemuck::cast_slice(&l.data).to_vec(),
+		})
+		.collect();
+
+	let base = base_level(&byte_levels);
+	let layer = p
+		.layer()
+		.with_size(base.width, base.height)
+		.with_format(wgpu::TextureFormat::Rgba32Float)
+		.with_mips_max(levels.len() as u32)
+		.with_static_texture_data(&base.data)
+		.create();
+
+	write_mip_levels(p, layer, &byte_levels, 16);
+	layer
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn chain_sizes() {
+		let levels = mip_chain_rgba_f32(10, 3, &vec![0.5; 10 * 3 * 4], MipFilter::Box);
+		let sizes: Vec<_> = levels.iter().map(|l| (l.width, l.height)).collect();
+		assert_eq!(sizes, vec![(10, 3), (5, 1), (2, 1), (1, 1)]);
+		for l in &levels {
+			assert_eq!(l.data.len(), (l.width * l.height * 4) as usize);
+		}
+	}
+
+	#[test]
+	fn filters_preserve_constant() {
+		let rgba = vec![0.25; 16 * 16 * 4];
+		for filter in [MipFilter::Box, MipFilter::Kaiser] {
+			for level in mip_chain_rgba_f32(16, 16, &rgba, filter) {
+				assert!(level.data.iter().all(|v| (v - 0.25).abs() < 1e-5));
+			}
+		}
+	}
+
+	#[test]
+	fn box_averages_quads() {
+		#[rustfmt::skip]
+		let rgba = [
+			0.0, 0.0, 0.0, 1.0,  1.0, 1.0, 1.0, 1.0,
+			1.0, 1.0, 1.0, 1.0,  0.0, 0.0, 0.0, 1.0,
+		];
+		let level = downsample_rgba_f32(2, 2, &rgba, MipFilter::Box);
+		assert_eq!(level.data, vec![0.5, 0.5, 0.5, 1.0]);
+	}
+
+	#[test]
+	fn srgb_averaging_is_gamma_correct() {
+		let rgba = [0, 0, 0, 255, 255, 255, 255, 255];
+		let linear = mip_chain_rgba_u8(2, 1, &rgba, MipFilter::Box, false);
+		let srgb = mip_chain_rgba_u8(2, 1, &rgba, MipFilter::Box, true);
+		assert_eq!(linear[1].data, vec![128, 128, 128, 255]);
+		// linear 0.5 encodes to ~188 in sRGB
+		assert_eq!(srgb[1].data, vec![188, 188, 188, 255]);
+	}
+
+	#[test]
+	fn single_level_chain() {
+		let levels = mip_chain_rgba_u8(1, 1, &[10, 20, 30, 255], MipFilter::Box, true);
+		assert_eq!(levels.len(), 1);
+		let base = base_level(&levels);
+		assert_eq!((base.width, base.height), (1, 1));
+		assert_eq!(base.data, vec![10, 20, 30, 255]);
+	}
+
+	#[test]
+	#[should_panic(expected = "at least the base level")]
+	fn empty_chain_panics() {
+		base_level::<u8>(&[]);
+	}
+}
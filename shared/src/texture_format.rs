@@ -0,0 +1,271 @@
+use trivalibs::painter::{Painter, layer::Layer, wgpu};
+
+// Unsigned float with a 5 bit exponent (bias 15) and `mantissa_bits` mantissa bits, as used
+// by f16 and the packed RG11B10 format. Rounds to nearest even, negative values become 0.
+fn f32_to_small_float(v: f32, mantissa_bits: u32) -> u32 {
+	let bits = v.to_bits();
+	let exp = (bits >> 23) & 0xff;
+	let man = bits & 0x7fffff;
+	let inf = 0x1f << mantissa_bits;
+
+	let nan = exp == 0xff && man != 0;
+	if bits & 0x8000_0000 != 0 && !nan {
+		return 0;
+	}
+	if exp == 0xff {
+		// keep NaN a NaN
+		return inf | (nan as u32) << (mantissa_bits - 1);
+	}
+
+	let e = exp as i32 - 112;
+	if e >= 0x1f {
+		return inf;
+	}
+
+	let round = |value: u32, rest: u32, shift: u32| {
+		let halfway = 1 << (shift - 1);
+		if rest > halfway || (rest == halfway && value & 1 == 1) {
+			value + 1
+		} else {
+			value
+		}
+	};
+
+	let drop = 23 - mantissa_bits;
+	if e <= 0 {
+		if e < -(mantissa_bits as i32) {
+			return 0;
+		}
+		let man = man | 0x800000;
+		let shift = drop + (1 - e) as u32;
+		return round(man >> shift, man & ((1 << shift) - 1), shift);
+	}
+
+	// a carry out of the mantissa correctly bumps the exponent, up to infinity
+	round(
+		((e as u32) << mantissa_bits) | (man >> drop),
+		man & ((1 << drop) - 1),
+		drop,
+	)
+}
+
+fn small_float_to_f32(v: u32, mantissa_bits: u32) -> f32 {
+	let exp = (v >> mantissa_bits) & 0x1f;
+	let man = v & ((1 << mantissa_bits) - 1);
+	let scale = (1 << mantissa_bits) as f32;
+	match exp {
+		0 => man as f32 / scale * 2f32.powi(-14),
+		0x1f if man == 0 => f32::INFINITY,
+		0x1f => f32::NAN,
+		_ => (1.0 + man as f32 / scale) * 2f32.powi(exp as i32 - 15),
+	}
+}
+
+/// IEEE half float bits, rounded to nearest even.
+pub fn f32_to_f16(v: f32) -> u16 {
+	let sign = ((v.to_bits() >> 16) & 0x8000) as u16;
+	sign | f32_to_small_float(v.abs(), 10) as u16
+}
+
+pub fn f16_to_f32(v: u16) -> f32 {
+	let abs = small_float_to_f32((v & 0x7fff) as u32, 10);
+	if v & 0x8000 != 0 { -abs } else { abs }
+}
+
+fn unorm(v: f32, max: u32) -> u32 {
+	(v.clamp(0.0, 1.0) * max as f32).round() as u32
+}
+
+/// 10 bit red, green, blue and 2 bit alpha, red in the lowest bits.
+pub fn pack_rgb10a2(r: f32, g: f32, b: f32, a: f32) -> u32 {
+	unorm(r, 1023) | unorm(g, 1023) << 10 | unorm(b, 1023) << 20 | unorm(a, 3) << 30
+}
+
+/// Unsigned 11 bit floats for red and green and a 10 bit float for blue, red in the
+/// lowest bits. Negative values are clamped to 0.
+pub fn pack_rg11b10(r: f32, g: f32, b: f32) -> u32 {
+	f32_to_small_float(r, 6) | f32_to_small_float(g, 6) << 11 | f32_to_small_float(b, 5) << 22
+}
+
+pub fn unpack_rg11b10(v: u32) -> [f32; 3] {
+	[
+		small_float_to_f32(v & 0x7ff, 6),
+		small_float_to_f32((v >> 11) & 0x7ff, 6),
+		small_float_to_f32(v >> 22, 5),
+	]
+}
+
+/// Texture formats that RGBA f32 data can be converted to on the CPU.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TexelFormat {
+	Rgba8Unorm,
+	Rgba16Float,
+	Rgba32Float,
+	/// Alpha is quantized to 2 bits.
+	Rgb10a2Unorm,
+	/// Drops alpha, the colors must be positive.
+	Rg11b10Float,
+	/// Single channel formats keep the red channel.
+	R8Unorm,
+	/// Needs the `TEXTURE_FORMAT_16BIT_NORM` device feature.
+	R16Unorm,
+	R16Float,
+	/// Not filterable without the `FLOAT32_FILTERABLE` device feature.
+	R32Float,
+}
+
+impl TexelFormat {
+	pub fn wgpu_format(self) -> wgpu::TextureFormat {
+		match self {
+			Self::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+			Self::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+			Self::Rgba32Float => wgpu::TextureFormat::Rgba32Float,
+			Self::Rgb10a2Unorm => wgpu::TextureFormat::Rgb10a2Unorm,
+			Self::Rg11b10Float => wgpu::TextureFormat::Rg11b10Ufloat,
+			Self::R8Unorm => wgpu::TextureFormat::R8Unorm,
+			Self::R16Unorm => wgpu::TextureFormat::R16Unorm,
+			Self::R16Float => wgpu::TextureFormat::R16Float,
+			Self::R32Float => wgpu::TextureFormat::R32Float,
+		}
+	}
+
+	pub fn bytes_per_texel(self) -> usize {
+		match self {
+			Self::R8Unorm => 1,
+			Self::R16Unorm | Self::R16Float => 2,
+			Self::Rgba8Unorm | Self::Rgb10a2Unorm | Self::Rg11b10Float | Self::R32Float => 4,
+			Self::Rgba16Float => 8,
+			Self::Rgba32Float => 16,
+		}
+	}
+
+	/// Converts interleaved RGBA f32 data to the byte layout of this format.
+	pub fn encode(self, rgba: &[f32]) -> Vec<u8> {
+		let texels = rgba.chunks_exact(4);
+		match self {
+			Self::Rgba8Unorm => rgba.iter().map(|v| unorm(*v, 255) as u8).collect(),
+			Self::Rgba16Float => {
+				let half: Vec<u16> = rgba.iter().map(|v| f32_to_f16(*v)).collect();
+				bytemuck::cast_slice(&half).to_vec()
+			}
+			Self::Rgba32Float => bytemuck::cast_slice(rgba).to_vec(),
+			Self::Rgb10a2Unorm => {
+				let packed: Vec<u32> = texels
+					.map(|c| pack_rgb10a2(c[0], c[1], c[2], c[3]))
+					.collect();
+				bytemuck::cast_slice(&packed).to_vec()
+			}
+			Self::Rg11b10Float => {
+				let packed: Vec<u32> = texels.map(|c| pack_rg11b10(c[0], c[1], c[2])).collect();
+				bytemuck::cast_slice(&packed).to_vec()
+			}
+			Self::R8Unorm => texels.map(|c| unorm(c[0], 255) as u8).collect(),
+			Self::R16Unorm => {
+				let red: Vec<u16> = texels.map(|c| unorm(c[0], 65535) as u16).collect();
+				bytemuck::cast_slice(&red).to_vec()
+			}
+			Self::R16Float => {
+				let half: Vec<u16> = texels.map(|c| f32_to_f16(c[0])).collect();
+				bytemuck::cast_slice(&half).to_vec()
+			}
+			Self::R32Float => {
+				let red: Vec<f32> = texels.map(|c| c[0]).collect();
+				bytemuck::cast_slice(&red).to_vec()
+			}
+		}
+	}
+}
+
+/// Uploads RGBA f32 data as static texture, converted to `format`.
+pub fn texture_from_rgba_f32(
+	p: &mut Painter,
+	width: u32,
+	height: u32,
+	rgba: &[f32],
+	format: TexelFormat,
+) -> Layer {
+	assert_eq!(rgba.len(), (width * height * 4) as usize);
+	p.layer()
+		.with_size(width, height)
+		.with_format(format.wgpu_format())
+		.with_static_texture_data(&format.encode(rgba))
+		.create()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn half_float_conversion() {
+		assert_eq!(f32_to_f16(0.0), 0x0000);
+		assert_eq!(f32_to_f16(-0.0), 0x8000);
+		assert_eq!(f32_to_f16(1.0), 0x3c00);
+		assert_eq!(f32_to_f16(-2.0), 0xc000);
+		assert_eq!(f32_to_f16(65504.0), 0x7bff);
+		assert_eq!(f32_to_f16(1e6), 0x7c00);
+		assert_eq!(f32_to_f16(f32::NEG_INFINITY), 0xfc00);
+		assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+		// smallest subnormal
+		assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+		assert_eq!(f32_to_f16(2f32.powi(-26)), 0x0000);
+		// ties round to even
+		assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+		assert_eq!(f32_to_f16(1.0 + 3.0 * 2f32.powi(-11)), 0x3c02);
+
+		for i in 0..=0x7bff_u16 {
+			assert_eq!(f32_to_f16(f16_to_f32(i)), i);
+		}
+	}
+
+	#[test]
+	fn packed_formats() {
+		assert_eq!(pack_rgb10a2(1.0, 0.0, 0.0, 0.0), 0x3ff);
+		assert_eq!(pack_rgb10a2(0.0, 0.0, 0.0, 1.0), 0xc000_0000);
+		assert_eq!(pack_rgb10a2(0.0, 1.0, 1.0, 0.0), 0x3fff_fc00);
+
+		// 1.0 has exponent 15 and no mantissa
+		assert_eq!(pack_rg11b10(1.0, 0.0, 0.0), 15 << 6);
+		assert_eq!(pack_rg11b10(0.0, 0.0, 1.0), (15 << 5) << 22);
+		assert_eq!(pack_rg11b10(-1.0, 0.0, 0.0), 0);
+		assert_eq!(pack_rg11b10(f32::NEG_INFINITY, 0.0, f32::NEG_INFINITY), 0);
+		assert_eq!(pack_rg11b10(f32::INFINITY, 0.0, 0.0), 0x1f << 6);
+		let [r, g, _] = unpack_rg11b10(pack_rg11b10(-f32::NAN, f32::NAN, 0.0));
+		assert!(r.is_nan() && g.is_nan());
+		let [r, g, b] = unpack_rg11b10(pack_rg11b10(0.5, 3.25, 100.0));
+		assert_eq!((r, g, b), (0.5, 3.25, 100.0));
+		let [r, _, _] = unpack_rg11b10(pack_rg11b10(0.3, 0.0, 0.0));
+		assert!((r - 0.3).abs() < 0.3 / 64.0);
+	}
+
+	#[test]
+	fn encoded_sizes() {
+		let rgba = [0.25, 0.5, 0.75, 1.0, 1.0, 0.0, 0.0, 0.5];
+		for format in [
+			TexelFormat::Rgba8Unorm,
+			TexelFormat::Rgba16Float,
+			TexelFormat::Rgba32Float,
+			TexelFormat::Rgb10a2Unorm,
+			TexelFormat::Rg11b10Float,
+			TexelFormat::R8Unorm,
+			TexelFormat::R16Unorm,
+			TexelFormat::R16Float,
+			TexelFormat::R32Float,
+		] {
+			assert_eq!(format.encode(&rgba).len(), 2 * format.bytes_per_texel());
+		}
+		assert_eq!(TexelFormat::R8Unorm.encode(&rgba), vec![64, 255]);
+		assert_eq!(
+			TexelFormat::R16Unorm.encode(&rgba),
+			[16384u16.to_le_bytes(), 65535u16.to_le_bytes()].concat()
+		);
+		assert_eq!(
+			TexelFormat::R16Float.encode(&rgba),
+			[
+				f32_to_f16(0.25).to_le_bytes(),
+				f32_to_f16(1.0).to_le_bytes()
+			]
+			.concat()
+		);
+	}
+}
@@ -0,0 +1,30 @@
+use shared::{
+	cpu_render::{read_png_rgba, render},
+	golden::{Tolerance, UPDATE_ENV, check_golden_with},
+};
+use std::{fs, path::PathBuf};
+use trivalibs::prelude::*;
+
+#[test]
+fn golden_round_trip_and_diff_output() {
+	let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden_round_trip");
+	let _ = fs::remove_dir_all(&dir);
+	let check =
+		|image, update| check_golden_with(image, "gradient", &dir, &dir, Tolerance::default(), update);
+	let gradient = render(16, 8, 0.0, |uv, _, _| vec4(uv.x, uv.y, 0.5, 1.0));
+
+	// missing references fail with a hint
+	assert!(check(&gradient, false).unwrap_err().contains(UPDATE_ENV));
+
+	// updating writes the reference, which then matches
+	assert!(check(&gradient, true).is_ok());
+	let (width, height, rgba) = read_png_rgba(dir.join("gradient.png")).unwrap();
+	assert_eq!((width, height), (16, 8));
+	assert_eq!(rgba, gradient.to_rgba_u8());
+	assert!(check(&gradient, false).is_ok());
+
+	let changed = render(16, 8, 0.0, |uv, _, _| vec4(uv.y, uv.x, 0.5, 1.0));
+	assert!(check(&changed, false).is_err());
+	assert!(dir.join("gradient.diff.png").exists());
+	assert!(dir.join("gradient.actual.png").exists());
+}
@@ -27,3 +27,19 @@ pub fn pool_tiles(
 ) {
 	*out = shaders::pool_tiles::shader(uv, *size, *time);
 }
+
+/// The fragment entry points above in display order, see `tex_tests_shader::fragment_entries`.
+#[macro_export]
+macro_rules! fragment_entries {
+	($m:ident) => {
+		$m! {
+			pool_tiles: SizeTime, true;
+			moving_plates: SizeTime, true;
+		}
+	};
+}
+
+#[cfg(not(target_arch = "spirv"))]
+use shared_nostd::shader_entries;
+#[cfg(not(target_arch = "spirv"))]
+fragment_entries!(shader_entries);
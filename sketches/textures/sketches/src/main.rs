@@ -32,11 +32,19 @@ impl CanvasApp<()> for App {
 			(s, layer)
 		};
 
-		let (s, canvas_tiles) = shade_canvas(p);
-		load_fragment_shader!(s, p, "../shader/out/moving_plates.spv");
+		// The shader paths have to be known at compile time, so the entry table of the shader
+		// crate is expanded into one layer per entry point.
+		macro_rules! layers {
+			($($name:ident: SizeTime, $animated:literal;)*) => {
+				vec![$({
+					let (s, layer) = shade_canvas(p);
+					load_fragment_shader!(s, p, concat!("../shader/out/", stringify!($name), ".spv"));
+					layer
+				}),*]
+			};
+		}
 
-		let (s, canvas_pool_tiles) = shade_canvas(p);
-		load_fragment_shader!(s, p, "../shader/out/pool_tiles.spv");
+		let layers = tex_sketches_shader::fragment_entries!(layers);
 
 		// return App
 
@@ -45,7 +53,7 @@ impl CanvasApp<()> for App {
 			u_size,
 			u_time,
 
-			layers: vec![canvas_pool_tiles, canvas_tiles],
+			layers,
 			current_layer: 0,
 		}
 	}
@@ -150,3 +150,33 @@ pub fn bos_shapes_circles(
 ) {
 	*out = book_of_shaders::circles::shader(uv, *time);
 }
+
+/// The fragment entry points above in display order, with their bindings and whether they
+/// are animated. Passes the table to the macro `$m`, so the host can create a canvas for
+/// each entry and load it from `out/<name>.spv`. Adding a shader only needs a line here.
+#[macro_export]
+macro_rules! fragment_entries {
+	($m:ident) => {
+		$m! {
+			circular_line: SizeTime, true;
+			noisy_lines_2: SizeTime, true;
+			net: SizeTime, false;
+			tiled_lines: SizeTime, true;
+			noisy_lines_1: SizeTime, true;
+			bos_shapes_circles: SizeTime, true;
+			bos_shapes_circle: SizeTime, true;
+			bos_shapes_rect: SizeTime, false;
+			bos_colors: SizeTime, true;
+			bos_shaping_fns: SizeTime, false;
+			fbm_shader: SizeTime, true;
+			simplex_prefilled: SizeTexture, false;
+			bos_shapes_rounded_rect: SizeTime, false;
+			noisy_quads: SizeTime, true;
+		}
+	};
+}
+
+#[cfg(not(target_arch = "spirv"))]
+use shared_nostd::shader_entries;
+#[cfg(not(target_arch = "spirv"))]
+fragment_entries!(shader_entries);
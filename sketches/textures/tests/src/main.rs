@@ -71,47 +71,28 @@ impl CanvasApp<()> for App {
 			(s, Canvas { layer, animated })
 		};
 
-		let (s, canvas_fbm_shader) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/fbm_shader.spv");
-
-		let (s, canvas_circular_line) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/circular_line.spv");
-
-		let (s, canvas_simplex_prefilled) = texture_shade_canvas(p, noise_tex, false);
-		load_fragment_shader!(s, p, "../shader/out/simplex_prefilled.spv");
-
-		let (s, canvas_bos_shaping_fns) = shade_canvas(p, false);
-		load_fragment_shader!(s, p, "../shader/out/bos_shaping_fns.spv");
-
-		let (s, canvas_bos_colors) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/bos_colors.spv");
-
-		let (s, canvas_bos_shapes_rect) = shade_canvas(p, false);
-		load_fragment_shader!(s, p, "../shader/out/bos_shapes_rect.spv");
-
-		let (s, canvas_bos_shapes_rounded_rect) = shade_canvas(p, false);
-		load_fragment_shader!(s, p, "../shader/out/bos_shapes_rounded_rect.spv");
-
-		let (s, canvas_bos_shapes_circle) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/bos_shapes_circle.spv");
-
-		let (s, canvas_bos_shapes_circles) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/bos_shapes_circles.spv");
-
-		let (s, canvas_noisy_lines_1) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/noisy_lines_1.spv");
-
-		let (s, canvas_tiled_lines) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/tiled_lines.spv");
-
-		let (s, canvas_noisy_lines_2) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/noisy_lines_2.spv");
+		// The shader paths have to be known at compile time, so the entry table of the shader
+		// crate is expanded into one canvas per entry point.
+		macro_rules! canvas {
+			($name:ident, SizeTime, $animated:literal) => {{
+				let (s, canvas) = shade_canvas(p, $animated);
+				load_fragment_shader!(s, p, concat!("../shader/out/", stringify!($name), ".spv"));
+				canvas
+			}};
+			($name:ident, SizeTexture, $animated:literal) => {{
+				let (s, canvas) = texture_shade_canvas(p, noise_tex, $animated);
+				load_fragment_shader!(s, p, concat!("../shader/out/", stringify!($name), ".spv"));
+				canvas
+			}};
+		}
 
-		let (s, canvas_noisy_quads) = shade_canvas(p, true);
-		load_fragment_shader!(s, p, "../shader/out/noisy_quads.spv");
+		macro_rules! canvases {
+			($($name:ident: $bindings:ident, $animated:literal;)*) => {
+				vec![$(canvas!($name, $bindings, $animated)),*]
+			};
+		}
 
-		let (s, canvas_net) = shade_canvas(p, false);
-		load_fragment_shader!(s, p, "../shader/out/net.spv");
+		let canvases = tex_tests_shader::fragment_entries!(canvases);
 
 		// return App
 
@@ -120,22 +101,7 @@ impl CanvasApp<()> for App {
 			u_size,
 			u_time,
 
-			canvases: vec![
-				canvas_circular_line,
-				canvas_noisy_lines_2,
-				canvas_net,
-				canvas_tiled_lines,
-				canvas_noisy_lines_1,
-				canvas_bos_shapes_circles,
-				canvas_bos_shapes_circle,
-				canvas_bos_shapes_rect,
-				canvas_bos_colors,
-				canvas_bos_shaping_fns,
-				canvas_fbm_shader,
-				canvas_simplex_prefilled,
-				canvas_bos_shapes_rounded_rect,
-				canvas_noisy_quads,
-			],
+			canvases,
 			current_canvas: 0,
 		}
 	}
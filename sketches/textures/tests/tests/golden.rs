@@ -0,0 +1,38 @@
+// Renders every CPU capable entry point of `tex_tests_shader` at a fixed size and times
+// and compares the result against the PNGs in `tests/golden`. Static entry points are
+// rendered at every time as well, so an accidental dependency on time shows up.
+//
+// Regenerate the references after an intended change with
+// `UPDATE_GOLDEN=1 cargo test -p tex_tests --test golden`. Failing shaders write the
+// rendered image and a diff next to the test binaries, the path is in the failure message.
+
+use shared::{
+	cpu_render::render,
+	golden::{Tolerance, check_golden},
+};
+use std::path::{Path, PathBuf};
+use tex_tests_shader::ENTRIES;
+
+const WIDTH: u32 = 96;
+const HEIGHT: u32 = 64;
+const TIMES: [f32; 2] = [0.0, 2.5];
+
+#[test]
+fn shaders_match_golden_images() {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+	let diff_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden-diff");
+
+	let mut failures = vec![];
+	for entry in ENTRIES {
+		let Some(shader) = entry.cpu else { continue };
+		for (i, &time) in TIMES.iter().enumerate() {
+			let image = render(WIDTH, HEIGHT, time, shader);
+			let golden = format!("{}_{i}", entry.name);
+			if let Err(e) = check_golden(&image, &golden, &dir, &diff_dir, Tolerance::default()) {
+				failures.push(e);
+			}
+		}
+	}
+
+	assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}